@@ -0,0 +1,31 @@
+use bevy::prelude::*;
+
+/// Headless AI benchmark scene: many enemies and a few scripted players, no networking
+#[derive(Resource)]
+pub struct AiBench {
+    pub enemies: usize,
+    pub players: usize,
+    pub archetype: String,
+    /// Set once the scene is spawned, measuring starts after that
+    pub spawned: bool,
+}
+
+impl AiBench {
+    pub fn new(enemies: usize) -> Self {
+        Self {
+            enemies,
+            players: 8,
+            archetype: "grunt".to_string(),
+            spawned: false,
+        }
+    }
+}
+
+/// Scripted player running laps around the arena so enemies keep chasing
+#[derive(Component)]
+pub struct BenchPlayer {
+    pub angle: f32,
+    pub radius: f32,
+    /// Radians per second
+    pub speed: f32,
+}
@@ -0,0 +1,5 @@
+pub mod components;
+pub mod systems;
+
+pub use components::AiBench;
+pub use systems::*;
@@ -0,0 +1,101 @@
+use bevy::prelude::*;
+use rand::Rng;
+use std::time::Duration;
+
+use super::components::{AiBench, BenchPlayer};
+use crate::game::enemy::archetype::{EnemyArchetype, EnemyArchetypes, EnemyScaling, EnemySpawn};
+use crate::game::enemy::components::PatrolData;
+use crate::game::enemy::lod::AiLodMetrics;
+use crate::game::enemy::spawn_enemy;
+use crate::game::navigation::NavMesh;
+use crate::network::protocol::{Health, Player, PlayerPosition, PlayerStats};
+
+/// Spawn the benchmark enemies and players once archetypes and the navmesh are ready
+pub fn spawn_ai_bench(
+    mut commands: Commands,
+    mut bench: ResMut<AiBench>,
+    archetype_assets: Res<Assets<EnemyArchetype>>,
+    archetypes: Res<EnemyArchetypes>,
+    navmesh: Option<Res<NavMesh>>,
+) {
+    if bench.spawned {
+        return;
+    }
+    let (Some(navmesh), Some(archetype)) = (navmesh, archetypes.get(&archetype_assets, &bench.archetype)) else {
+        return;
+    };
+
+    let spawn = EnemySpawn::new(archetype, EnemyScaling::default());
+    let mut rng = rand::thread_rng();
+    let mut spawned = 0;
+    while spawned < bench.enemies {
+        let position = Vec3::new(rng.gen_range(-45.0..45.0), 1.0, rng.gen_range(-45.0..45.0));
+        // Don't start inside an obstacle
+        if !navmesh.cell_of(position).is_some_and(|cell| navmesh.is_walkable(cell)) {
+            continue;
+        }
+        spawn_enemy(&mut commands, spawned as u32 + 1, &spawn, position, PatrolData::new(position, 5.0));
+        spawned += 1;
+    }
+
+    for index in 0..bench.players {
+        let angle = index as f32 * std::f32::consts::TAU / bench.players as f32;
+        commands.spawn((
+            Player {
+                id: index as u64,
+                color_index: index as u8,
+            },
+            PlayerPosition { x: 0.0, y: 1.0, z: 0.0 },
+            PlayerStats::default(),
+            // Nobody respawns in the benchmark, so players can't die
+            Health {
+                current: f32::MAX,
+                max: f32::MAX,
+            },
+            Transform::from_xyz(0.0, 1.0, 0.0),
+            BenchPlayer {
+                angle,
+                radius: 10.0 + 4.0 * index as f32,
+                speed: 0.2,
+            },
+        ));
+    }
+
+    bench.spawned = true;
+    println!("[BENCH] Spawned {} '{}' enemies and {} players", bench.enemies, bench.archetype, bench.players);
+}
+
+/// Move the scripted players in circles around the arena
+pub fn move_ai_bench_players(mut players: Query<(&mut BenchPlayer, &mut Transform)>, time: Res<Time>) {
+    for (mut player, mut transform) in players.iter_mut() {
+        player.angle += player.speed * time.delta_secs();
+        transform.translation = Vec3::new(player.angle.cos() * player.radius, 1.0, player.angle.sin() * player.radius);
+    }
+}
+
+/// Print tick time statistics against the server's tick budget
+pub fn print_ai_bench_report(enemies: usize, samples: &[Duration], budget: Duration, lod: &AiLodMetrics) {
+    if samples.is_empty() {
+        println!("[BENCH] No ticks measured");
+        return;
+    }
+
+    let mut sorted = samples.to_vec();
+    sorted.sort();
+    let millis = |duration: Duration| duration.as_secs_f64() * 1000.0;
+    let percentile = |p: f64| sorted[((sorted.len() - 1) as f64 * p).round() as usize];
+    let average = sorted.iter().sum::<Duration>() / sorted.len() as u32;
+    let over_budget = sorted.iter().filter(|sample| **sample > budget).count();
+
+    println!("\n=== AI benchmark: {} enemies, {} ticks ===", enemies, sorted.len());
+    println!("Tick time: avg {:.2}ms, p50 {:.2}ms, p99 {:.2}ms, max {:.2}ms",
+        millis(average), millis(percentile(0.5)), millis(percentile(0.99)), millis(sorted[sorted.len() - 1]));
+    println!("Over the {:.2}ms budget: {} ticks ({:.1}%)",
+        millis(budget), over_budget, over_budget as f64 * 100.0 / sorted.len() as f64);
+    println!("AI LOD {}", lod.summary());
+    if percentile(0.99) <= budget {
+        println!("Stable: 99% of ticks fit the budget");
+    } else {
+        println!("Unstable: more than 1% of ticks miss the budget");
+    }
+}
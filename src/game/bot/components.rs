@@ -0,0 +1,56 @@
+use bevy::prelude::*;
+
+/// Bot player ids start here so they never collide with netcode connection timestamps
+pub const BOT_ID_BASE: u64 = 1 << 62;
+
+/// How many players the server keeps in the arena with bots (server-only)
+#[derive(Resource)]
+pub struct BotSettings {
+    /// Bots fill up to this many players and leave as humans join, 0 disables bots
+    pub target_players: usize,
+}
+
+impl Default for BotSettings {
+    fn default() -> Self {
+        Self { target_players: 4 }
+    }
+}
+
+/// Marker for Player entities controlled by a server-side bot (server-only)
+#[derive(Component)]
+pub struct BotPlayer;
+
+/// Stand-in client entity for a bot (server-only).
+/// It carries a NetworkId so the bot's commands resolve like a real client's.
+#[derive(Component)]
+pub struct BotClient {
+    pub player_id: u64,
+}
+
+/// Decision state of a bot (server-only)
+#[derive(Component)]
+pub struct BotBrain {
+    /// Point the bot walks to when it has nothing to shoot at
+    pub wander_target: Vec3,
+    pub next_wander: f32,
+    pub shoot_cooldown: f32,
+    /// Seconds between shots
+    pub fire_interval: f32,
+    /// Distance within which the bot engages targets
+    pub engage_range: f32,
+    /// Random aim error in degrees
+    pub aim_error: f32,
+}
+
+impl Default for BotBrain {
+    fn default() -> Self {
+        Self {
+            wander_target: Vec3::ZERO,
+            next_wander: 0.0,
+            shoot_cooldown: 1.0,
+            fire_interval: 0.6,
+            engage_range: 15.0,
+            aim_error: 3.0,
+        }
+    }
+}
@@ -0,0 +1,5 @@
+pub mod components;
+pub mod systems;
+
+pub use components::{BotPlayer, BotSettings};
+pub use systems::{balance_bots, drive_bots};
@@ -0,0 +1,201 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_replicon::prelude::*;
+use bevy_replicon::shared::backend::connected_client::NetworkId;
+use rand::Rng;
+
+use super::components::{BotBrain, BotClient, BotPlayer, BotSettings, BOT_ID_BASE};
+use crate::game::player::{spawn_player, systems::NeedsRespawn};
+use crate::game::world::state::PlayerCount;
+use crate::network::protocol::{Enemy, Health, MovementInput, Player, RotationInput, ShootEvent, MAX_PLAYERS};
+
+/// Same eye height the client camera uses above the player origin
+const EYE_HEIGHT: f32 = 1.6;
+/// Bots wander inside this radius around the origin
+const WANDER_RADIUS: f32 = 15.0;
+/// Bots back off when a target gets closer than this
+const MIN_ENGAGE_DISTANCE: f32 = 4.0;
+
+/// Add or remove one bot per tick so humans + bots matches the configured player count (server-side)
+pub fn balance_bots(
+    mut commands: Commands,
+    settings: Res<BotSettings>,
+    mut player_count: ResMut<PlayerCount>,
+    mut next_bot_id: Local<u64>,
+    humans: Query<&Player, Without<BotPlayer>>,
+    bot_players: Query<(Entity, &Player), With<BotPlayer>>,
+    bot_clients: Query<(Entity, &BotClient)>,
+) {
+    let human_count = humans.iter().count();
+    let bot_count = bot_clients.iter().count();
+    let wanted = settings.target_players.min(MAX_PLAYERS).saturating_sub(human_count);
+
+    if bot_count < wanted {
+        let bot_id = BOT_ID_BASE + *next_bot_id;
+        *next_bot_id += 1;
+
+        let player_entity = spawn_player(&mut commands, &mut player_count, bot_id);
+        commands.entity(player_entity).insert(BotPlayer);
+        commands.spawn((
+            BotClient { player_id: bot_id },
+            BotBrain::default(),
+            NetworkId::new(bot_id),
+        ));
+
+        println!("[SERVER] Bot {} joined ({} humans, {} bots)", bot_id, human_count, bot_count + 1);
+    } else if bot_count > wanted {
+        // Newest bot leaves first
+        let Some((client_entity, bot)) = bot_clients.iter().max_by_key(|(_, bot)| bot.player_id) else {
+            return;
+        };
+
+        for (player_entity, player) in bot_players.iter() {
+            if player.id == bot.player_id {
+                commands.entity(player_entity).despawn();
+            }
+        }
+        commands.entity(client_entity).despawn();
+
+        println!("[SERVER] Bot {} left ({} humans, {} bots)", bot.player_id, human_count, bot_count - 1);
+    }
+}
+
+/// Bots decide what to do and send the same commands a client would (server-side).
+/// The commands are consumed by handle_rotation_input, handle_movement_input and handle_shoot_events.
+pub fn drive_bots(
+    time: Res<Time>,
+    mut bots: Query<(Entity, &BotClient, &mut BotBrain)>,
+    players: Query<(Entity, &Player, &Transform, Has<NeedsRespawn>)>,
+    enemies: Query<(Entity, &Transform, &Health), With<Enemy>>,
+    rapier_context: ReadRapierContext,
+    mut rotation_writer: MessageWriter<FromClient<RotationInput>>,
+    mut movement_writer: MessageWriter<FromClient<MovementInput>>,
+    mut shoot_writer: MessageWriter<FromClient<ShootEvent>>,
+) {
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    let mut rng = rand::thread_rng();
+    let delta = time.delta_secs();
+
+    for (client_entity, bot, mut brain) in bots.iter_mut() {
+        let Some((bot_entity, _, bot_transform, is_dead)) = players
+            .iter()
+            .find(|(_, player, _, _)| player.id == bot.player_id)
+        else {
+            continue;
+        };
+
+        if is_dead {
+            continue;
+        }
+
+        brain.shoot_cooldown -= delta;
+        brain.next_wander -= delta;
+
+        let eye = bot_transform.translation + Vec3::Y * EYE_HEIGHT;
+        let engage_range = brain.engage_range;
+
+        // Line of sight check, the ray must hit the target before anything else
+        let can_see = |target: Entity, position: Vec3| {
+            let offset = position - eye;
+            let filter = QueryFilter::default().exclude_rigid_body(bot_entity);
+            rapier_context.with_query_pipeline(filter, |query_pipeline| {
+                query_pipeline
+                    .cast_ray(eye, offset.normalize_or_zero(), offset.length() + 1.0, true)
+                    .is_some_and(|(hit_entity, _)| hit_entity == target)
+            })
+        };
+
+        let closest_visible = |targets: &mut dyn Iterator<Item = (Entity, Vec3)>| {
+            targets
+                .filter(|(_, position)| eye.distance(*position) <= engage_range)
+                .filter(|(entity, position)| can_see(*entity, *position))
+                .min_by(|(_, a), (_, b)| eye.distance(*a).total_cmp(&eye.distance(*b)))
+        };
+
+        // Prefer enemies, otherwise fight other players
+        let target = closest_visible(
+            &mut enemies
+                .iter()
+                .filter(|(_, _, health)| health.current > 0.0)
+                .map(|(entity, transform, _)| (entity, transform.translation)),
+        )
+            .or_else(|| {
+                closest_visible(
+                    &mut players
+                        .iter()
+                        .filter(|(entity, _, _, is_dead)| *entity != bot_entity && !is_dead)
+                        .map(|(entity, _, transform, _)| (entity, transform.translation)),
+                )
+            });
+
+        let (aim_direction, movement) = match target {
+            Some((_, target_position)) => {
+                let distance = eye.distance(target_position);
+                let forward = if distance < MIN_ENGAGE_DISTANCE {
+                    -1.0
+                } else if distance > engage_range * 0.5 {
+                    1.0
+                } else {
+                    0.0
+                };
+                // Strafe, switching sides every two seconds
+                let right = if brain.next_wander.rem_euclid(4.0) < 2.0 { 1.0 } else { -1.0 };
+                ((target_position - eye).normalize_or_zero(), Vec2::new(forward, right))
+            }
+            None => {
+                let mut to_wander = brain.wander_target - bot_transform.translation;
+                to_wander.y = 0.0;
+
+                if brain.next_wander <= 0.0 || to_wander.length() < 1.0 {
+                    let angle: f32 = rng.r#gen::<f32>() * std::f32::consts::TAU;
+                    let radius = rng.gen_range(0.0..WANDER_RADIUS);
+                    brain.wander_target = Vec3::new(angle.cos() * radius, 0.0, angle.sin() * radius);
+                    brain.next_wander = rng.gen_range(3.0..8.0);
+                }
+
+                (to_wander.normalize_or_zero(), Vec2::new(1.0, 0.0))
+            }
+        };
+
+        if aim_direction == Vec3::ZERO {
+            continue;
+        }
+
+        // Same yaw/pitch convention as the client camera (degrees, -Z forward)
+        let mut yaw = (-aim_direction.x).atan2(-aim_direction.z).to_degrees();
+        let mut pitch = aim_direction.y.clamp(-1.0, 1.0).asin().to_degrees();
+
+        rotation_writer.write(FromClient {
+            client_id: ClientId::Client(client_entity),
+            message: RotationInput { yaw, pitch },
+        });
+        movement_writer.write(FromClient {
+            client_id: ClientId::Client(client_entity),
+            message: MovementInput {
+                forward: movement.x,
+                right: movement.y,
+            },
+        });
+
+        if target.is_none() || brain.shoot_cooldown > 0.0 {
+            continue;
+        }
+        brain.shoot_cooldown = brain.fire_interval;
+
+        yaw += rng.gen_range(-brain.aim_error..=brain.aim_error);
+        pitch += rng.gen_range(-brain.aim_error..=brain.aim_error);
+        let rotation = Quat::from_axis_angle(Vec3::Y, yaw.to_radians())
+            * Quat::from_axis_angle(Vec3::X, pitch.to_radians());
+
+        shoot_writer.write(FromClient {
+            client_id: ClientId::Client(client_entity),
+            message: ShootEvent {
+                origin: eye,
+                direction: rotation * Vec3::NEG_Z,
+            },
+        });
+    }
+}
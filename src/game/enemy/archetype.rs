@@ -0,0 +1,306 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext, LoadedFolder},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
+
+use super::boss::{BossState, EnemyBoss};
+use super::behavior_tree::{default_tree, BehaviorNode, Blackboard, EnemyBehaviorTree};
+use super::components::{EnemyAttack, EnemyBehavior, EnemyMovement, EnemyFlight, EnemyPerception, EnemyRanged, EnemyThreat, FlockingBehavior};
+use crate::game::ron_asset::{read_ron, RonAssetError};
+use crate::network::protocol::{Enemy, EnemyAppearance, Health};
+
+/// Folder (under assets/) with one `.enemy.ron` file per archetype
+const ARCHETYPE_FOLDER: &str = "enemies";
+
+/// Enemy archetype loaded from an `.enemy.ron` asset file (server-only)
+#[derive(Asset, TypePath, Deserialize, Clone)]
+pub struct EnemyArchetype {
+    /// Name used to reference the archetype when spawning
+    pub name: String,
+    pub max_health: f32,
+    #[serde(default)]
+    pub movement: EnemyMovement,
+    #[serde(default)]
+    pub flocking: FlockingBehavior,
+    #[serde(default)]
+    pub attack: EnemyAttack,
+    /// Fires projectiles instead of hitting in melee when set
+    #[serde(default)]
+    pub ranged: Option<EnemyRanged>,
+    /// Flies as a 3D boid instead of walking when set
+    #[serde(default)]
+    pub flight: Option<EnemyFlight>,
+    /// Scripted phases, makes the enemy a boss when set
+    #[serde(default)]
+    pub boss: Option<EnemyBoss>,
+    #[serde(default)]
+    pub perception: EnemyPerception,
+    #[serde(default)]
+    pub behavior: EnemyBehavior,
+    #[serde(default)]
+    pub threat: EnemyThreat,
+    /// Behavior tree the enemy runs, the default melee tree when missing
+    #[serde(default = "default_tree")]
+    pub tree: BehaviorNode,
+    /// Capsule collider radius
+    pub radius: f32,
+    /// Capsule collider half height (without the caps)
+    pub half_height: f32,
+    /// Scene rendered by clients instead of the default capsule
+    #[serde(default)]
+    pub model: Option<String>,
+    pub color: (f32, f32, f32),
+}
+
+#[derive(Default, TypePath)]
+pub struct EnemyArchetypeLoader;
+
+impl AssetLoader for EnemyArchetypeLoader {
+    type Asset = EnemyArchetype;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        read_ron(reader).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["enemy.ron"]
+    }
+}
+
+/// Loaded archetypes by name (server-only)
+#[derive(Resource, Default)]
+pub struct EnemyArchetypes {
+    /// Held so every archetype in the folder stays loaded and watched for changes
+    _folder: Handle<LoadedFolder>,
+    by_name: HashMap<String, AssetId<EnemyArchetype>>,
+}
+
+impl EnemyArchetypes {
+    pub fn get<'a>(&self, assets: &'a Assets<EnemyArchetype>, name: &str) -> Option<&'a EnemyArchetype> {
+        self.by_name.get(name).and_then(|id| assets.get(*id))
+    }
+
+    pub fn is_loaded(&self) -> bool {
+        !self.by_name.is_empty()
+    }
+}
+
+/// Wave and director multipliers applied on top of the archetype (server-only)
+/// Kept on the enemy so hot reloads can re-apply them to the new values.
+#[derive(Component, Clone, Copy)]
+pub struct EnemyScaling {
+    pub health: f32,
+    pub damage: f32,
+    pub speed: f32,
+    /// Sight and hearing distance, how far away players get noticed and chased from
+    pub range: f32,
+}
+
+impl Default for EnemyScaling {
+    fn default() -> Self {
+        Self {
+            health: 1.0,
+            damage: 1.0,
+            speed: 1.0,
+            range: 1.0,
+        }
+    }
+}
+
+/// Name of the archetype an enemy was spawned from (server-only)
+#[derive(Component)]
+pub struct EnemyArchetypeName(pub String);
+
+/// Stats an enemy is spawned with: an archetype with its scaling applied (server-only)
+#[derive(Clone)]
+pub struct EnemySpawn {
+    pub archetype: String,
+    pub scaling: EnemyScaling,
+    pub max_health: f32,
+    pub movement: EnemyMovement,
+    pub flocking: FlockingBehavior,
+    pub attack: EnemyAttack,
+    pub ranged: Option<EnemyRanged>,
+    pub flight: Option<EnemyFlight>,
+    pub boss: Option<EnemyBoss>,
+    pub perception: EnemyPerception,
+    pub behavior: EnemyBehavior,
+    pub threat: EnemyThreat,
+    pub tree: BehaviorNode,
+    pub appearance: EnemyAppearance,
+}
+
+impl EnemySpawn {
+    pub fn new(archetype: &EnemyArchetype, scaling: EnemyScaling) -> Self {
+        let mut movement = archetype.movement.clone();
+        movement.patrol_speed *= scaling.speed;
+        movement.chase_speed *= scaling.speed;
+
+        let mut attack = archetype.attack.clone();
+        attack.damage *= scaling.damage;
+
+        let ranged = archetype.ranged.clone().map(|mut ranged| {
+            ranged.strafe_speed *= scaling.speed;
+            ranged
+        });
+        let flight = archetype.flight.clone().map(|mut flight| {
+            flight.dive_speed *= scaling.speed;
+            flight
+        });
+
+        let mut perception = archetype.perception.clone();
+        perception.view_distance *= scaling.range;
+        perception.hearing_radius *= scaling.range;
+
+        Self {
+            archetype: archetype.name.clone(),
+            scaling,
+            max_health: archetype.max_health * scaling.health,
+            movement,
+            flocking: archetype.flocking.clone(),
+            attack,
+            ranged,
+            flight,
+            boss: archetype.boss.clone(),
+            perception,
+            behavior: archetype.behavior.clone(),
+            threat: archetype.threat.clone(),
+            tree: archetype.tree.clone(),
+            appearance: EnemyAppearance {
+                model: archetype.model.clone(),
+                color: [archetype.color.0, archetype.color.1, archetype.color.2],
+                radius: archetype.radius,
+                half_height: archetype.half_height,
+            },
+        }
+    }
+
+    pub fn collider(&self) -> Collider {
+        Collider::capsule_y(self.appearance.half_height, self.appearance.radius)
+    }
+}
+
+/// Start loading every archetype file (server-side)
+pub fn load_enemy_archetypes(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(EnemyArchetypes {
+        _folder: asset_server.load_folder(ARCHETYPE_FOLDER),
+        by_name: HashMap::new(),
+    });
+}
+
+/// Keep the name index up to date and push edited archetypes to living enemies (server-side)
+pub fn reload_enemy_archetypes(
+    mut events: MessageReader<AssetEvent<EnemyArchetype>>,
+    assets: Res<Assets<EnemyArchetype>>,
+    mut archetypes: ResMut<EnemyArchetypes>,
+    mut enemies: Query<(
+        &EnemyArchetypeName,
+        &EnemyScaling,
+        &mut Health,
+        &mut EnemyMovement,
+        &mut FlockingBehavior,
+        &mut EnemyAttack,
+        (Option<&mut EnemyRanged>, Option<&mut EnemyFlight>, Option<(&mut EnemyBoss, &mut BossState)>),
+        &mut EnemyPerception,
+        &mut EnemyBehavior,
+        &mut EnemyThreat,
+        (&mut EnemyBehaviorTree, &mut Blackboard),
+        &mut Collider,
+        &mut EnemyAppearance,
+    ), With<Enemy>>,
+) {
+    let mut index_changed = false;
+    let mut modified = Vec::new();
+
+    for event in events.read() {
+        match event {
+            AssetEvent::Added { .. } | AssetEvent::Removed { .. } => index_changed = true,
+            AssetEvent::Modified { id } => {
+                index_changed = true;
+                modified.push(*id);
+            }
+            _ => {}
+        }
+    }
+
+    if index_changed {
+        archetypes.by_name = assets
+            .iter()
+            .map(|(id, archetype)| (archetype.name.clone(), id))
+            .collect();
+        println!("[SERVER] Enemy archetypes: {:?}", archetypes.by_name.keys().collect::<Vec<_>>());
+    }
+
+    for id in modified {
+        let Some(archetype) = assets.get(id) else {
+            continue;
+        };
+
+        let mut updated = 0;
+        for (
+            name,
+            scaling,
+            mut health,
+            mut movement,
+            mut flocking,
+            mut attack,
+            (ranged, flight, boss),
+            mut perception,
+            mut behavior,
+            mut threat,
+            (mut tree, mut blackboard),
+            mut collider,
+            mut appearance,
+        ) in enemies.iter_mut()
+        {
+            if name.0 != archetype.name {
+                continue;
+            }
+
+            let spawn = EnemySpawn::new(archetype, *scaling);
+            // Keep the same fraction of health, corpses stay dead
+            if health.current > 0.0 {
+                health.current = spawn.max_health * (health.current / health.max);
+            }
+            health.max = spawn.max_health;
+            *collider = spawn.collider();
+            *movement = spawn.movement.clone();
+            *flocking = spawn.flocking;
+            *attack = spawn.attack.clone();
+            // Switching between melee, ranged and flying only applies to newly spawned enemies
+            if let (Some(mut ranged), Some(spawn_ranged)) = (ranged, spawn.ranged) {
+                *ranged = spawn_ranged;
+            }
+            if let (Some(mut flight), Some(spawn_flight)) = (flight, spawn.flight) {
+                *flight = spawn_flight;
+            }
+            // Bosses keep their phase, its multipliers are applied to the new values
+            if let (Some((mut boss, mut boss_state)), Some(spawn_boss)) = (boss, spawn.boss) {
+                *boss = spawn_boss;
+                boss_state.base_movement = spawn.movement.clone();
+                boss_state.base_attack = spawn.attack.clone();
+                boss_state.dirty = true;
+            }
+            *perception = spawn.perception;
+            *behavior = spawn.behavior;
+            *threat = spawn.threat;
+            // Node indices change with the tree, running sequences start over
+            tree.0 = spawn.tree;
+            blackboard.progress.clear();
+            *appearance = spawn.appearance;
+            updated += 1;
+        }
+
+        println!("[SERVER] Reloaded enemy archetype '{}' ({} enemies updated)", archetype.name, updated);
+    }
+}
@@ -0,0 +1,417 @@
+use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::{HashMap, HashSet};
+
+use super::components::{
+    advance_waypoint, get_current_waypoint, resume_at_nearest_waypoint, EnemyAwareness, EnemyBehavior, EnemyMovement, EnemyPerception,
+    EnemyState, EnemyStateTimer, PatrolData,
+};
+use crate::game::navigation::NavPath;
+use crate::network::protocol::Health;
+
+/// Result of ticking a node
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Status {
+    Success,
+    Failure,
+    Running,
+}
+
+/// Behavior tree node, authored in the `tree` field of an archetype file
+#[derive(Deserialize, Clone, Debug)]
+pub enum BehaviorNode {
+    /// Runs children in order until one fails. Remembers the running child between ticks,
+    /// conditions before it are checked again every tick.
+    Sequence(Vec<BehaviorNode>),
+    /// Runs children in order until one doesn't fail, starting from the first every tick
+    Selector(Vec<BehaviorNode>),
+    /// Swaps success and failure
+    Invert(Box<BehaviorNode>),
+    /// Turns failure into success
+    Succeed(Box<BehaviorNode>),
+    /// Fails while the named cooldown runs, starts it when the child succeeds
+    Cooldown(String, f32, Box<BehaviorNode>),
+    Condition(Condition),
+    Action(Action),
+}
+
+/// Leaf checking the blackboard or what the enemy perceives, never running
+#[derive(Deserialize, Clone, Debug)]
+pub enum Condition {
+    TargetVisible,
+    TargetInAttackRange,
+    /// Health at or below this fraction of max health
+    HealthBelow(f32),
+    HasLastKnownPosition,
+    InState(EnemyState),
+    Flag(String),
+}
+
+/// Leaf driving one of the enemy behaviours, selected through `EnemyState`
+#[derive(Deserialize, Clone, Debug)]
+pub enum Action {
+    /// Walk to the next patrol waypoint, succeeds on arrival
+    Patrol,
+    /// Stand still for `idle_time`
+    Idle,
+    /// Go after the target, or its last known position once lost. Succeeds when the trail goes cold.
+    Chase,
+    /// Attack the target while it's in range, fails otherwise
+    Attack,
+    /// Look around the last known position for `search_time`
+    Search,
+    /// Run away from the last known position for `flee_time`
+    Flee,
+    /// Running while a stun lasts, fails otherwise
+    Stunned,
+    /// Drop the target and its last known position
+    Forget,
+    SetFlag(String),
+    ClearFlag(String),
+}
+
+/// Per-enemy memory of the tree (server-only)
+#[derive(Component, Default)]
+pub struct Blackboard {
+    pub flags: HashSet<String>,
+    /// Time (seconds since startup) at which each named cooldown ends
+    pub cooldowns: HashMap<String, f32>,
+    /// Running child of each sequence, by node index in pre-order
+    pub progress: HashMap<usize, usize>,
+    /// Path to the leaf that ran last tick, for debugging
+    pub active: String,
+}
+
+/// Prints the active node of every enemy's tree on each tick of the timer, inserted when BT_DEBUG is set (server-only)
+#[derive(Resource)]
+pub struct BehaviorTreeDebug(pub Timer);
+
+/// The tree an enemy runs, copied from its archetype (server-only)
+#[derive(Component, Clone)]
+pub struct EnemyBehaviorTree(pub BehaviorNode);
+
+/// Everything a leaf can look at or change while ticking one enemy
+pub struct BehaviorContext<'a> {
+    pub position: Vec3,
+    pub health: &'a Health,
+    pub state: EnemyState,
+    /// State the enemy ends up in, written by actions
+    pub next_state: EnemyState,
+    pub timer: &'a EnemyStateTimer,
+    pub movement: &'a EnemyMovement,
+    pub perception: &'a EnemyPerception,
+    pub behavior: &'a EnemyBehavior,
+    pub awareness: &'a mut EnemyAwareness,
+    pub patrol: &'a mut PatrolData,
+    pub path: &'a mut NavPath,
+    /// Distance to the player in sight
+    pub target_distance: Option<f32>,
+    pub now: f32,
+}
+
+impl BehaviorNode {
+    /// Number of nodes in this subtree, used to give every node a stable index
+    fn size(&self) -> usize {
+        match self {
+            BehaviorNode::Sequence(children) | BehaviorNode::Selector(children) => {
+                1 + children.iter().map(BehaviorNode::size).sum::<usize>()
+            }
+            BehaviorNode::Invert(child) | BehaviorNode::Succeed(child) | BehaviorNode::Cooldown(_, _, child) => {
+                1 + child.size()
+            }
+            BehaviorNode::Condition(_) | BehaviorNode::Action(_) => 1,
+        }
+    }
+
+    fn is_condition(&self) -> bool {
+        match self {
+            BehaviorNode::Condition(_) => true,
+            BehaviorNode::Invert(child) => child.is_condition(),
+            _ => false,
+        }
+    }
+
+    fn label(&self, index: usize) -> String {
+        match self {
+            BehaviorNode::Sequence(_) => format!("Sequence#{}", index),
+            BehaviorNode::Selector(_) => format!("Selector#{}", index),
+            BehaviorNode::Invert(_) => "Invert".to_string(),
+            BehaviorNode::Succeed(_) => "Succeed".to_string(),
+            BehaviorNode::Cooldown(name, _, _) => format!("Cooldown({})", name),
+            BehaviorNode::Condition(condition) => format!("{:?}", condition),
+            BehaviorNode::Action(action) => format!("{:?}", action),
+        }
+    }
+}
+
+/// Tick the whole tree once for one enemy
+pub fn tick_tree(root: &BehaviorNode, blackboard: &mut Blackboard, context: &mut BehaviorContext) -> Status {
+    let mut tick = Tick {
+        progress: std::mem::take(&mut blackboard.progress),
+        next_progress: HashMap::new(),
+        path: Vec::new(),
+        active: None,
+        flags: &mut blackboard.flags,
+        cooldowns: &mut blackboard.cooldowns,
+    };
+    let status = tick.node(root, 0, context);
+
+    blackboard.progress = tick.next_progress;
+    blackboard.active = tick.active.unwrap_or_else(|| format!("{:?}", status));
+    status
+}
+
+// State of a single tick. Sequences that aren't running this tick lose their progress.
+struct Tick<'a> {
+    progress: HashMap<usize, usize>,
+    next_progress: HashMap<usize, usize>,
+    path: Vec<String>,
+    active: Option<String>,
+    flags: &'a mut HashSet<String>,
+    cooldowns: &'a mut HashMap<String, f32>,
+}
+
+impl Tick<'_> {
+    fn node(&mut self, node: &BehaviorNode, index: usize, context: &mut BehaviorContext) -> Status {
+        self.path.push(node.label(index));
+        let status = match node {
+            BehaviorNode::Sequence(children) => self.sequence(children, index, context),
+            BehaviorNode::Selector(children) => {
+                let mut child_index = index + 1;
+                let mut status = Status::Failure;
+                for child in children {
+                    status = self.node(child, child_index, context);
+                    if status != Status::Failure {
+                        break;
+                    }
+                    child_index += child.size();
+                }
+                status
+            }
+            BehaviorNode::Invert(child) => match self.node(child, index + 1, context) {
+                Status::Success => Status::Failure,
+                Status::Failure => Status::Success,
+                Status::Running => Status::Running,
+            },
+            BehaviorNode::Succeed(child) => match self.node(child, index + 1, context) {
+                Status::Running => Status::Running,
+                _ => Status::Success,
+            },
+            BehaviorNode::Cooldown(name, seconds, child) => {
+                if self.cooldowns.get(name).is_some_and(|ready_at| context.now < *ready_at) {
+                    Status::Failure
+                } else {
+                    let status = self.node(child, index + 1, context);
+                    if status == Status::Success {
+                        self.cooldowns.insert(name.clone(), context.now + seconds);
+                    }
+                    status
+                }
+            }
+            BehaviorNode::Condition(condition) => {
+                if check_condition(condition, self.flags, context) {
+                    Status::Success
+                } else {
+                    Status::Failure
+                }
+            }
+            BehaviorNode::Action(action) => {
+                let status = run_action(action, self.flags, context);
+                if status == Status::Running && self.active.is_none() {
+                    self.active = Some(self.path.join(" > "));
+                }
+                status
+            }
+        };
+        self.path.pop();
+        status
+    }
+
+    fn sequence(&mut self, children: &[BehaviorNode], index: usize, context: &mut BehaviorContext) -> Status {
+        let offsets: Vec<usize> = children
+            .iter()
+            .scan(index + 1, |next, child| {
+                let offset = *next;
+                *next += child.size();
+                Some(offset)
+            })
+            .collect();
+
+        let resume = self.progress.get(&index).copied().unwrap_or(0).min(children.len());
+        // Conditions guarding the running child still have to hold
+        for (child, offset) in children[..resume].iter().zip(&offsets) {
+            if child.is_condition() && self.node(child, *offset, context) != Status::Success {
+                return Status::Failure;
+            }
+        }
+
+        for (child_index, child) in children.iter().enumerate().skip(resume) {
+            match self.node(child, offsets[child_index], context) {
+                Status::Success => continue,
+                Status::Failure => return Status::Failure,
+                Status::Running => {
+                    self.next_progress.insert(index, child_index);
+                    return Status::Running;
+                }
+            }
+        }
+        Status::Success
+    }
+}
+
+fn check_condition(condition: &Condition, flags: &HashSet<String>, context: &BehaviorContext) -> bool {
+    match condition {
+        Condition::TargetVisible => context.target_distance.is_some(),
+        Condition::TargetInAttackRange => context
+            .target_distance
+            .is_some_and(|distance| distance <= context.movement.attack_range),
+        Condition::HealthBelow(fraction) => context.health.current <= context.health.max * fraction,
+        Condition::HasLastKnownPosition => context.awareness.last_known_position.is_some(),
+        Condition::InState(state) => context.state == *state,
+        Condition::Flag(name) => flags.contains(name),
+    }
+}
+
+// Start a timed state, or keep running it until `duration` has passed in it
+fn timed_state(context: &mut BehaviorContext, state: EnemyState, duration: f32) -> Status {
+    if context.state != state {
+        context.next_state = state;
+        Status::Running
+    } else if context.timer.elapsed < duration {
+        context.next_state = state;
+        Status::Running
+    } else {
+        Status::Success
+    }
+}
+
+fn run_action(action: &Action, flags: &mut HashSet<String>, context: &mut BehaviorContext) -> Status {
+    match action {
+        Action::Patrol => {
+            // Back from a fight, walk to the closest waypoint instead of the one it left off at
+            if context.patrol.interrupted {
+                resume_at_nearest_waypoint(context.patrol, context.position);
+                context.path.clear();
+            }
+
+            // A finished path means the waypoint is reached, or as close as the navmesh allows.
+            // Only the ground position counts, flyers pass over their waypoints
+            let waypoint = get_current_waypoint(context.patrol);
+            let path_done =
+                context.state == EnemyState::Patrol && context.path.goal.is_some() && context.path.is_finished();
+            let arrived = context.patrol.waiting || context.position.xz().distance(waypoint.xz()) < 0.5 || path_done;
+            if !arrived {
+                context.next_state = EnemyState::Patrol;
+                return Status::Running;
+            }
+
+            // The default square ends here so the tree can idle, authored routes wait as long as
+            // the level says and keep walking
+            if context.patrol.route.is_none() {
+                advance_waypoint(context.patrol);
+                context.path.clear();
+                return Status::Success;
+            }
+            let wait = context.patrol.waits.get(context.patrol.current_waypoint).copied().unwrap_or(0.0);
+            if wait > 0.0 {
+                context.patrol.waiting = true;
+                if timed_state(context, EnemyState::Idle, wait) == Status::Running {
+                    return Status::Running;
+                }
+            }
+            context.patrol.waiting = false;
+            advance_waypoint(context.patrol);
+            context.path.clear();
+            context.next_state = EnemyState::Patrol;
+            Status::Running
+        }
+        Action::Idle => timed_state(context, EnemyState::Idle, context.behavior.idle_time),
+        Action::Chase => {
+            let Some(last_known) = context.awareness.last_known_position else {
+                return Status::Failure;
+            };
+            let arrived = context.position.xz().distance(last_known.xz()) < 1.0;
+            let chased_too_long = context.awareness.since_noticed > context.perception.memory;
+            if context.target_distance.is_none() && (arrived || chased_too_long) {
+                return Status::Success;
+            }
+            context.next_state = EnemyState::Chase;
+            Status::Running
+        }
+        Action::Attack => {
+            if context
+                .target_distance
+                .is_some_and(|distance| distance <= context.movement.attack_range)
+            {
+                context.next_state = EnemyState::Attack;
+                Status::Running
+            } else {
+                Status::Failure
+            }
+        }
+        Action::Search => {
+            if context.awareness.last_known_position.is_none() {
+                return Status::Failure;
+            }
+            timed_state(context, EnemyState::Search, context.behavior.search_time)
+        }
+        Action::Flee => {
+            if context.state != EnemyState::Flee && context.awareness.last_known_position.is_none() {
+                return Status::Failure;
+            }
+            timed_state(context, EnemyState::Flee, context.behavior.flee_time)
+        }
+        Action::Stunned => {
+            if context.state == EnemyState::Stunned && context.timer.elapsed < context.behavior.stun_time {
+                Status::Running
+            } else {
+                Status::Failure
+            }
+        }
+        Action::Forget => {
+            context.awareness.forget();
+            Status::Success
+        }
+        Action::SetFlag(name) => {
+            flags.insert(name.clone());
+            Status::Success
+        }
+        Action::ClearFlag(name) => {
+            flags.remove(name);
+            Status::Success
+        }
+    }
+}
+
+/// Tree used by archetypes without a `tree`, the classic melee enemy
+pub fn default_tree() -> BehaviorNode {
+    use BehaviorNode as N;
+
+    let fled = || "fled".to_string();
+    N::Selector(vec![
+        N::Action(Action::Stunned),
+        // Run away once per life when badly hurt
+        N::Sequence(vec![
+            N::Invert(Box::new(N::Condition(Condition::Flag(fled())))),
+            N::Selector(vec![
+                N::Condition(Condition::InState(EnemyState::Flee)),
+                N::Sequence(vec![
+                    N::Condition(Condition::TargetVisible),
+                    N::Condition(Condition::HealthBelow(0.25)),
+                ]),
+            ]),
+            N::Action(Action::Flee),
+            N::Action(Action::SetFlag(fled())),
+        ]),
+        N::Sequence(vec![N::Condition(Condition::TargetInAttackRange), N::Action(Action::Attack)]),
+        N::Sequence(vec![N::Condition(Condition::TargetVisible), N::Action(Action::Chase)]),
+        // Lost the target or heard a shot: go there, look around, give up
+        N::Sequence(vec![
+            N::Condition(Condition::HasLastKnownPosition),
+            N::Action(Action::Chase),
+            N::Action(Action::Search),
+            N::Action(Action::Forget),
+        ]),
+        N::Sequence(vec![N::Action(Action::Patrol), N::Action(Action::Idle)]),
+    ])
+}
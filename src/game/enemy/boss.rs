@@ -0,0 +1,331 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use serde::Deserialize;
+
+use super::archetype::{EnemyArchetype, EnemyArchetypes, EnemyScaling, EnemySpawn};
+use super::components::{EnemyAttack, EnemyAwareness, EnemyMovement, EnemyState, EnemyTarget, PatrolData};
+use super::systems::spawn_enemy;
+use crate::game::player::{damage_player, systems::NeedsRespawn};
+use crate::game::wave::WaveDirector;
+use crate::network::protocol::{AreaAttackWarning, Boss, Enemy, Health, Player, PlayerStats};
+
+/// Scripted phases of a boss archetype (server-only)
+#[derive(Component, Clone, Deserialize)]
+pub struct EnemyBoss {
+    /// Ordered by falling `health_below`, the first phase should start at full health
+    pub phases: Vec<BossPhase>,
+}
+
+/// What a boss does while its health is at or below `health_below` (server-only)
+#[derive(Clone, Deserialize)]
+#[serde(default)]
+pub struct BossPhase {
+    /// Fraction of max health the phase starts at
+    pub health_below: f32,
+    /// Multipliers on the archetype's movement speeds, attack damage and attack cooldown
+    pub speed_scale: f32,
+    pub damage_scale: f32,
+    pub cooldown_scale: f32,
+    pub summon: Option<BossSummon>,
+    pub area_attack: Option<BossAreaAttack>,
+}
+
+impl Default for BossPhase {
+    fn default() -> Self {
+        Self {
+            health_below: 1.0,
+            speed_scale: 1.0,
+            damage_scale: 1.0,
+            cooldown_scale: 1.0,
+            summon: None,
+            area_attack: None,
+        }
+    }
+}
+
+/// Minions called in during a phase (server-only)
+#[derive(Clone, Deserialize)]
+pub struct BossSummon {
+    pub archetype: String,
+    /// Minions per summon
+    pub count: u32,
+    /// Seconds between summons, the first one comes right when the phase starts
+    pub interval: f32,
+    /// No more summons while this many minions are alive
+    pub max_alive: u32,
+}
+
+/// Telegraphed hit on the ground under the boss's target (server-only)
+#[derive(Clone, Deserialize)]
+pub struct BossAreaAttack {
+    pub radius: f32,
+    pub damage: f32,
+    /// Seconds the warning zone is shown before it hits
+    pub warning_time: f32,
+    /// Seconds between area attacks
+    pub interval: f32,
+}
+
+/// Current phase and ability timers of a boss (server-only)
+#[derive(Component)]
+pub struct BossState {
+    pub phase: usize,
+    /// Archetype movement and attack the phase multipliers apply to
+    pub base_movement: EnemyMovement,
+    pub base_attack: EnemyAttack,
+    /// Set when the phase multipliers need to be applied again, e.g. after a hot reload
+    pub dirty: bool,
+    pub since_summon: f32,
+    pub since_area_attack: f32,
+}
+
+impl BossState {
+    pub fn new(spawn: &EnemySpawn) -> Self {
+        Self {
+            phase: 0,
+            base_movement: spawn.movement.clone(),
+            base_attack: spawn.attack.clone(),
+            dirty: true,
+            // Summon as soon as a phase allows it
+            since_summon: f32::MAX,
+            since_area_attack: 0.0,
+        }
+    }
+}
+
+/// Minion summoned by a boss (server-only)
+#[derive(Component)]
+pub struct BossMinion {
+    pub boss: Entity,
+}
+
+/// Area attack waiting to hit (server-only)
+#[derive(Component)]
+pub struct PendingAreaAttack {
+    pub timer: Timer,
+    pub damage: f32,
+    pub enemy_id: u32,
+}
+
+/// Client-side warning zone animation
+#[derive(Component)]
+pub struct RenderedAreaWarning {
+    pub elapsed: f32,
+}
+
+/// Enter the next phase once health drops below its threshold and apply its multipliers (server-only)
+pub fn boss_phase_system(
+    mut bosses: Query<(
+        &Enemy,
+        &Health,
+        &EnemyState,
+        &EnemyBoss,
+        &mut BossState,
+        &mut Boss,
+        &mut EnemyMovement,
+        &mut EnemyAttack,
+    )>,
+) {
+    for (enemy, health, state, boss, mut boss_state, mut replicated, mut movement, mut attack) in bosses.iter_mut() {
+        if *state == EnemyState::Dead || boss.phases.is_empty() {
+            continue;
+        }
+
+        let fraction = health.current / health.max;
+        let phase = boss
+            .phases
+            .iter()
+            .rposition(|phase| fraction <= phase.health_below)
+            .unwrap_or(0)
+            .max(boss_state.phase)
+            .min(boss.phases.len() - 1);
+
+        if phase != boss_state.phase {
+            boss_state.phase = phase;
+            boss_state.since_summon = f32::MAX;
+            boss_state.since_area_attack = 0.0;
+            boss_state.dirty = true;
+            replicated.phase = phase as u8;
+            println!("[SERVER] Boss {} ({}) entered phase {}", enemy.id, replicated.name, phase + 1);
+        }
+
+        if boss_state.dirty {
+            boss_state.dirty = false;
+            let current = &boss.phases[phase];
+            movement.patrol_speed = boss_state.base_movement.patrol_speed * current.speed_scale;
+            movement.chase_speed = boss_state.base_movement.chase_speed * current.speed_scale;
+            attack.damage = boss_state.base_attack.damage * current.damage_scale;
+            attack.cooldown = boss_state.base_attack.cooldown * current.cooldown_scale;
+        }
+    }
+}
+
+/// Summon minions and start area attacks while the boss is fighting (server-only)
+pub fn boss_ability_system(
+    mut commands: Commands,
+    mut bosses: Query<(
+        Entity,
+        &Enemy,
+        &Transform,
+        &EnemyState,
+        &EnemyBoss,
+        &mut BossState,
+        &EnemyScaling,
+        &EnemyTarget,
+        &EnemyAwareness,
+    )>,
+    minions: Query<(&BossMinion, &EnemyState)>,
+    players: Query<&Transform, (With<Player>, Without<Enemy>)>,
+    director: Option<ResMut<WaveDirector>>,
+    archetype_assets: Res<Assets<EnemyArchetype>>,
+    archetypes: Option<Res<EnemyArchetypes>>,
+    time: Res<Time>,
+) {
+    // Minions take their ids from the wave director so they count towards the wave
+    let (Some(mut director), Some(archetypes)) = (director, archetypes) else {
+        return;
+    };
+
+    for (entity, enemy, transform, state, boss, mut boss_state, scaling, target, awareness) in bosses.iter_mut() {
+        let fighting = matches!(*state, EnemyState::Chase | EnemyState::Attack);
+        if *state == EnemyState::Dead || !fighting {
+            continue;
+        }
+        let Some(phase) = boss.phases.get(boss_state.phase) else {
+            continue;
+        };
+        boss_state.since_summon += time.delta_secs();
+        boss_state.since_area_attack += time.delta_secs();
+
+        if let Some(summon) = &phase.summon {
+            let alive = minions
+                .iter()
+                .filter(|(minion, state)| minion.boss == entity && **state != EnemyState::Dead)
+                .count() as u32;
+            let archetype = archetypes.get(&archetype_assets, &summon.archetype);
+            if boss_state.since_summon >= summon.interval && alive < summon.max_alive {
+                if let Some(archetype) = archetype {
+                    boss_state.since_summon = 0.0;
+                    let spawn = EnemySpawn::new(archetype, *scaling);
+                    let count = summon.count.min(summon.max_alive - alive);
+                    for index in 0..count {
+                        let angle = index as f32 * std::f32::consts::TAU / count as f32;
+                        let position = transform.translation + Vec3::new(angle.cos(), 0.0, angle.sin()) * 3.0;
+                        let minion = spawn_enemy(&mut commands, director.next_enemy_id, &spawn, position, PatrolData::new(position, 5.0));
+                        commands.entity(minion).insert(BossMinion { boss: entity });
+                        director.next_enemy_id += 1;
+                    }
+                    println!("[SERVER] Boss {} summoned {} {}", enemy.id, count, summon.archetype);
+                } else {
+                    warn!("Unknown enemy archetype '{}'", summon.archetype);
+                }
+            }
+        }
+
+        // Area attacks land where the target stands when the warning goes up
+        let Some(area_attack) = &phase.area_attack else {
+            continue;
+        };
+        let target_position = target
+            .player
+            .filter(|player| awareness.target == Some(*player))
+            .and_then(|player| players.get(player).ok())
+            .map(|player_transform| player_transform.translation);
+        if boss_state.since_area_attack < area_attack.interval {
+            continue;
+        }
+        let Some(target_position) = target_position else {
+            continue;
+        };
+        boss_state.since_area_attack = 0.0;
+        commands.spawn((
+            AreaAttackWarning {
+                x: target_position.x,
+                y: target_position.y,
+                z: target_position.z,
+                radius: area_attack.radius,
+                duration: area_attack.warning_time,
+            },
+            PendingAreaAttack {
+                timer: Timer::from_seconds(area_attack.warning_time, TimerMode::Once),
+                damage: area_attack.damage,
+                enemy_id: enemy.id,
+            },
+            Replicated,
+        ));
+    }
+}
+
+/// Hit every player inside a warning zone once its timer runs out (server-only)
+pub fn resolve_area_attacks(
+    mut commands: Commands,
+    mut attacks: Query<(Entity, &AreaAttackWarning, &mut PendingAreaAttack)>,
+    mut players: Query<
+        (Entity, &Player, &Transform, &mut Health, &mut PlayerStats),
+        (Without<Enemy>, Without<NeedsRespawn>),
+    >,
+    time: Res<Time>,
+) {
+    for (entity, warning, mut pending) in attacks.iter_mut() {
+        if !pending.timer.tick(time.delta()).is_finished() {
+            continue;
+        }
+        commands.entity(entity).despawn();
+
+        let center = Vec3::new(warning.x, warning.y, warning.z);
+        for (player_entity, player, player_transform, mut health, mut stats) in players.iter_mut() {
+            let offset = player_transform.translation - center;
+            // Jumping doesn't save you, leaving the zone does
+            if offset.xz().length() > warning.radius || offset.y.abs() > 3.0 || health.current <= 0.0 {
+                continue;
+            }
+
+            if damage_player(&mut commands, player_entity, &mut health, Some(&mut *stats), pending.damage) {
+                println!("[SERVER] Boss {} area attack killed player {}", pending.enemy_id, player.id);
+            } else {
+                println!("[SERVER] Boss {} area attack hit player {} (Health: {:.0}/{:.0})",
+                    pending.enemy_id, player.id, health.current, health.max);
+            }
+        }
+    }
+}
+
+/// Draw warning zones as red discs on the ground (client-side)
+pub fn render_area_attack_warnings(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    warnings: Query<(Entity, &AreaAttackWarning), Without<RenderedAreaWarning>>,
+) {
+    for (entity, warning) in warnings.iter() {
+        commands.entity(entity).insert((
+            RenderedAreaWarning { elapsed: 0.0 },
+            Mesh3d(meshes.add(Cylinder::new(warning.radius, 0.05))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgba(1.0, 0.1, 0.0, 0.2),
+                alpha_mode: AlphaMode::Blend,
+                unlit: true,
+                ..default()
+            })),
+            // Zones are placed where the target's body was, draw them on its feet
+            Transform::from_xyz(warning.x, warning.y - 0.9, warning.z),
+            Visibility::default(),
+        ));
+    }
+}
+
+/// Make warning zones more opaque as the hit gets closer (client-side)
+pub fn animate_area_attack_warnings(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut warnings: Query<(&AreaAttackWarning, &mut RenderedAreaWarning, &MeshMaterial3d<StandardMaterial>)>,
+    time: Res<Time>,
+) {
+    for (warning, mut rendered, material) in warnings.iter_mut() {
+        rendered.elapsed += time.delta_secs();
+        let Some(material) = materials.get_mut(&material.0) else {
+            continue;
+        };
+        let progress = (rendered.elapsed / warning.duration.max(0.01)).min(1.0);
+        material.base_color = Color::srgba(1.0, 0.1, 0.0, 0.2 + 0.6 * progress);
+    }
+}
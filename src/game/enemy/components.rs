@@ -1,18 +1,100 @@
 use bevy::prelude::*;
+use serde::Deserialize;
+use std::collections::HashMap;
 
-/// FSM states for enemy (server-only, not replicated)
-#[derive(Component, Debug, Clone, Copy, PartialEq)]
-pub enum EnemyState {
-    Patrol,
-    Chase,
-    Attack,
+// FSM state is replicated so clients can animate it
+pub use crate::network::protocol::EnemyState;
+
+/// Time spent in the current state and the state it was in last tick (server-only)
+#[derive(Component)]
+pub struct EnemyStateTimer {
+    pub elapsed: f32,
+    pub previous: EnemyState,
+}
+
+impl Default for EnemyStateTimer {
+    fn default() -> Self {
+        Self {
+            elapsed: 0.0,
+            previous: EnemyState::Patrol,
+        }
+    }
 }
 
-/// Patrol waypoint index (server-only, pure data component)
+/// Sent whenever an enemy changes state (server-only)
+#[derive(Message, Clone, Copy)]
+pub struct EnemyStateChanged {
+    pub entity: Entity,
+    pub enemy_id: u32,
+    pub from: EnemyState,
+    pub to: EnemyState,
+}
+
+/// Timings and thresholds of the non-combat states, used by the behavior tree actions (server-only)
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnemyBehavior {
+    /// Seconds spent standing at each patrol waypoint
+    pub idle_time: f32,
+    /// Seconds spent looking around the last known position before giving up
+    pub search_time: f32,
+    /// Turn speed while looking around, in radians per second
+    pub search_turn_speed: f32,
+    /// Seconds spent running away
+    pub flee_time: f32,
+    /// How far ahead of the threat the flee target is placed
+    pub flee_distance: f32,
+    /// Hits adding up to at least this fraction of max health within `stun_window` seconds stun,
+    /// then the count starts over. Keep it above one hit's share so a single shot never stuns, 0 never stuns
+    pub stun_threshold: f32,
+    pub stun_window: f32,
+    pub stun_time: f32,
+    /// Seconds after a stun before the enemy can be stunned again, so focused fire can't stun-lock it
+    pub stun_cooldown: f32,
+}
+
+impl Default for EnemyBehavior {
+    fn default() -> Self {
+        Self {
+            idle_time: 2.0,
+            search_time: 6.0,
+            search_turn_speed: 1.5,
+            flee_time: 4.0,
+            flee_distance: 10.0,
+            stun_threshold: 0.5,
+            stun_window: 1.5,
+            stun_time: 0.5,
+            stun_cooldown: 3.0,
+        }
+    }
+}
+
+/// How an enemy walks its patrol route
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default, Deserialize)]
+pub enum PatrolMode {
+    /// Back to the first waypoint after the last one
+    #[default]
+    Loop,
+    /// Turn around at either end
+    PingPong,
+}
+
+/// Patrol route and progress along it (server-only, pure data component)
 #[derive(Component)]
 pub struct PatrolData {
     pub waypoints: Vec<Vec3>,
+    /// Seconds to stand at each waypoint, only used on authored routes
+    pub waits: Vec<f32>,
+    pub mode: PatrolMode,
+    /// Name of the level route, none for the default square where the behavior tree idles instead
+    pub route: Option<String>,
     pub current_waypoint: usize,
+    /// Walking a ping-pong route backwards
+    pub reversed: bool,
+    /// Standing at the current waypoint
+    pub waiting: bool,
+    /// Set when the enemy leaves its route to fight, it picks up at the nearest waypoint when it patrols again
+    pub interrupted: bool,
 }
 
 impl PatrolData {
@@ -25,9 +107,25 @@ impl PatrolData {
             center + Vec3::new(-radius, 0.0, radius),
         ];
 
+        Self::from_waypoints(waypoints, vec![0.0; 4], PatrolMode::Loop, None)
+    }
+
+    /// Route authored in the level, waypoints paired with the seconds to wait at them
+    pub fn route(name: &str, points: Vec<(Vec3, f32)>, mode: PatrolMode) -> Self {
+        let (waypoints, waits) = points.into_iter().unzip();
+        Self::from_waypoints(waypoints, waits, mode, Some(name.to_string()))
+    }
+
+    fn from_waypoints(waypoints: Vec<Vec3>, waits: Vec<f32>, mode: PatrolMode, route: Option<String>) -> Self {
         Self {
             waypoints,
+            waits,
+            mode,
+            route,
             current_waypoint: 0,
+            reversed: false,
+            waiting: false,
+            interrupted: false,
         }
     }
 }
@@ -38,13 +136,46 @@ pub fn get_current_waypoint(patrol: &PatrolData) -> Vec3 {
 }
 
 pub fn advance_waypoint(patrol: &mut PatrolData) {
-    patrol.current_waypoint = (patrol.current_waypoint + 1) % patrol.waypoints.len();
+    let count = patrol.waypoints.len();
+    if count < 2 {
+        return;
+    }
+    patrol.current_waypoint = match patrol.mode {
+        PatrolMode::Loop => (patrol.current_waypoint + 1) % count,
+        PatrolMode::PingPong => {
+            if patrol.current_waypoint == 0 {
+                patrol.reversed = false;
+            } else if patrol.current_waypoint == count - 1 {
+                patrol.reversed = true;
+            }
+            if patrol.reversed {
+                patrol.current_waypoint - 1
+            } else {
+                patrol.current_waypoint + 1
+            }
+        }
+    };
+}
+
+/// Continue the patrol from the waypoint closest to `position`, e.g. after a chase ended somewhere else
+pub fn resume_at_nearest_waypoint(patrol: &mut PatrolData, position: Vec3) {
+    let nearest = patrol
+        .waypoints
+        .iter()
+        .enumerate()
+        .min_by(|(_, a), (_, b)| a.xz().distance(position.xz()).total_cmp(&b.xz().distance(position.xz())))
+        .map(|(index, _)| index);
+    if let Some(nearest) = nearest {
+        patrol.current_waypoint = nearest;
+    }
+    patrol.waiting = false;
+    patrol.interrupted = false;
 }
 
 /// Enemy movement parameters (server-only)
-#[derive(Component)]
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
 pub struct EnemyMovement {
-    pub chase_range: f32,
     pub attack_range: f32,
     pub patrol_speed: f32,
     pub chase_speed: f32,
@@ -53,7 +184,6 @@ pub struct EnemyMovement {
 impl Default for EnemyMovement {
     fn default() -> Self {
         Self {
-            chase_range: 4.0,
             attack_range: 2.5,
             patrol_speed: 2.0,
             chase_speed: 4.0, // Slower than player
@@ -61,8 +191,135 @@ impl Default for EnemyMovement {
     }
 }
 
-/// Flocking behavior parameters (server-only)
+/// Enemy attack parameters (server-only)
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnemyAttack {
+    /// Damage dealt to the target player per hit
+    pub damage: f32,
+    /// Seconds between starting an attack and the hit landing
+    pub windup: f32,
+    /// Seconds after a hit before the next wind-up can start
+    pub cooldown: f32,
+}
+
+impl Default for EnemyAttack {
+    fn default() -> Self {
+        Self {
+            damage: 10.0,
+            windup: 0.6,
+            cooldown: 1.0,
+        }
+    }
+}
+
+/// Ranged attack parameters, enemies without them attack in melee (server-only)
+/// The shot uses `EnemyAttack` for damage, wind-up and cooldown, and `attack_range` is the firing range.
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnemyRanged {
+    /// Distance kept to the target while firing
+    pub preferred_distance: f32,
+    /// Sideways speed while firing
+    pub strafe_speed: f32,
+    /// Seconds before strafing changes direction
+    pub strafe_switch_time: f32,
+    pub projectile_speed: f32,
+    /// Downward acceleration of projectiles, 0 fires straight
+    pub projectile_gravity: f32,
+    pub projectile_radius: f32,
+    /// Seconds a projectile flies before it is removed
+    pub projectile_lifetime: f32,
+}
+
+impl Default for EnemyRanged {
+    fn default() -> Self {
+        Self {
+            preferred_distance: 10.0,
+            strafe_speed: 2.5,
+            strafe_switch_time: 1.5,
+            projectile_speed: 20.0,
+            projectile_gravity: 0.0,
+            projectile_radius: 0.15,
+            projectile_lifetime: 3.0,
+        }
+    }
+}
+
+/// Flight parameters, enemies with them fly as 3D boids instead of walking (server-only)
+/// Cruising uses `EnemyMovement` speeds, and `attack_range` is the distance a dive starts from.
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnemyFlight {
+    /// Height flyers climb back to when they drop below it, except while diving
+    pub min_altitude: f32,
+    pub max_altitude: f32,
+    /// Height flyers patrol and circle their target at
+    pub cruise_altitude: f32,
+    /// How quickly the velocity turns towards the desired one, per second
+    pub turn_rate: f32,
+    pub dive_speed: f32,
+    /// A dive hits once the flyer is this close to its target
+    pub strike_range: f32,
+    /// How far ahead obstacles are looked for
+    pub avoid_distance: f32,
+    pub avoid_weight: f32,
+    /// How hard flyers are pushed back between the altitude limits
+    pub altitude_weight: f32,
+}
+
+impl Default for EnemyFlight {
+    fn default() -> Self {
+        Self {
+            min_altitude: 3.0,
+            max_altitude: 12.0,
+            cruise_altitude: 6.0,
+            turn_rate: 3.0,
+            dive_speed: 11.0,
+            strike_range: 1.5,
+            avoid_distance: 5.0,
+            avoid_weight: 3.0,
+            altitude_weight: 1.0,
+        }
+    }
+}
+
+/// Which way a ranged enemy is strafing (server-only)
 #[derive(Component)]
+pub struct EnemyStrafe {
+    /// 1 strafes left, -1 right
+    pub direction: f32,
+    pub elapsed: f32,
+}
+
+impl Default for EnemyStrafe {
+    fn default() -> Self {
+        Self {
+            direction: 1.0,
+            elapsed: 0.0,
+        }
+    }
+}
+
+/// Where an enemy is in its attack cycle (server-only)
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum AttackPhase {
+    #[default]
+    Ready,
+    WindUp,
+    Cooldown,
+}
+
+/// Attack cycle progress (server-only)
+#[derive(Component, Default)]
+pub struct AttackTimer {
+    pub phase: AttackPhase,
+    pub elapsed: f32,
+}
+
+/// Flocking behavior parameters (server-only)
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
 pub struct FlockingBehavior {
     /// Range within which enemies are considered neighbors
     pub neighbor_range: f32,
@@ -88,10 +345,177 @@ impl Default for FlockingBehavior {
     }
 }
 
+/// Senses an enemy notices players with (server-only)
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnemyPerception {
+    /// How far the enemy can see
+    pub view_distance: f32,
+    /// Full angle of the view cone in degrees
+    pub field_of_view: f32,
+    /// Players this close are noticed even outside the view cone
+    pub awareness_radius: f32,
+    /// Gunshots within this distance are heard, walls don't block sound
+    pub hearing_radius: f32,
+    /// Seconds a lost target is chased to its last known position before searching
+    pub memory: f32,
+    /// Eye height above the enemy's origin, sight rays start here
+    pub eye_height: f32,
+}
+
+impl Default for EnemyPerception {
+    fn default() -> Self {
+        Self {
+            view_distance: 15.0,
+            field_of_view: 120.0,
+            awareness_radius: 2.0,
+            hearing_radius: 20.0,
+            memory: 5.0,
+            eye_height: 0.5,
+        }
+    }
+}
+
+/// What an enemy currently knows about players (server-only)
+#[derive(Component, Default)]
+pub struct EnemyAwareness {
+    /// The enemy's target while it is in sight
+    pub target: Option<Entity>,
+    /// Where a player was last seen or a gunshot was last heard
+    pub last_known_position: Option<Vec3>,
+    /// Seconds since the last sighting or sound
+    pub since_noticed: f32,
+}
+
+impl EnemyAwareness {
+    pub fn forget(&mut self) {
+        self.target = None;
+        self.last_known_position = None;
+    }
+}
+
+/// How an enemy builds up and sheds threat towards players (server-only)
+#[derive(Component, Clone, Deserialize)]
+#[serde(default)]
+pub struct EnemyThreat {
+    /// Threat per point of damage a player deals to this enemy
+    pub damage_threat: f32,
+    /// Threat per second from a visible player right next to the enemy, falls off to 0 at view distance
+    pub proximity_threat: f32,
+    /// Fraction of threat lost per second
+    pub decay: f32,
+    /// Another player needs this many times the current target's threat to take over while it's in sight
+    pub switch_margin: f32,
+    /// Minimum seconds between target switches
+    pub switch_cooldown: f32,
+}
+
+impl Default for EnemyThreat {
+    fn default() -> Self {
+        Self {
+            damage_threat: 1.0,
+            proximity_threat: 10.0,
+            decay: 0.1,
+            switch_margin: 1.25,
+            switch_cooldown: 2.0,
+        }
+    }
+}
+
+/// Threat accumulated per player (server-only)
+#[derive(Component, Default)]
+pub struct ThreatTable {
+    pub threat: HashMap<Entity, f32>,
+}
+
+impl ThreatTable {
+    pub fn add(&mut self, player: Entity, amount: f32) {
+        *self.threat.entry(player).or_default() += amount;
+    }
+
+    pub fn get(&self, player: Entity) -> f32 {
+        self.threat.get(&player).copied().unwrap_or(0.0)
+    }
+
+    pub fn highest(&self) -> Option<(Entity, f32)> {
+        self.threat
+            .iter()
+            .map(|(player, threat)| (*player, *threat))
+            .max_by(|(_, a), (_, b)| a.total_cmp(b))
+    }
+
+    pub fn clear(&mut self) {
+        self.threat.clear();
+    }
+}
+
+/// The player an enemy is after, read by perception, the behavior tree and attacks (server-only)
+#[derive(Component, Default)]
+pub struct EnemyTarget {
+    pub player: Option<Entity>,
+    /// Seconds since the target last changed
+    pub since_switch: f32,
+}
+
+/// Recent damage counting towards a stun (server-only)
+#[derive(Component, Default)]
+pub struct StunBuildup {
+    pub damage: f32,
+    /// Time of the last hit and of the last stun, in seconds since startup
+    pub last_hit: f32,
+    pub stunned_at: Option<f32>,
+}
+
+impl StunBuildup {
+    /// Count a hit and tell whether it stuns the enemy
+    pub fn add_hit(&mut self, behavior: &EnemyBehavior, damage: f32, max_health: f32, now: f32) -> bool {
+        if behavior.stun_threshold <= 0.0 {
+            return false;
+        }
+        if now - self.last_hit > behavior.stun_window {
+            self.damage = 0.0;
+        }
+        self.last_hit = now;
+        if self.stunned_at.is_some_and(|stunned_at| now - stunned_at < behavior.stun_cooldown) {
+            return false;
+        }
+
+        self.damage += damage;
+        if self.damage < max_health * behavior.stun_threshold {
+            return false;
+        }
+        self.damage = 0.0;
+        self.stunned_at = Some(now);
+        true
+    }
+}
+
+/// A shot fired by a player, enemies within hearing range react to it (server-only)
+#[derive(Message, Clone, Copy)]
+pub struct Gunshot {
+    pub origin: Vec3,
+}
+
+/// A player's shot hit a living enemy, feeds the pacing director (server-only)
+#[derive(Message, Clone, Copy)]
+pub struct EnemyHit {
+    pub killed: bool,
+}
+
 /// Velocity component for enemies (server-only)
 #[derive(Component, Default)]
 pub struct EnemyVelocity {
+    /// Flocking steering, blended into the path direction while chasing.
+    /// Flyers keep their actual 3D velocity here.
     pub velocity: Vec3,
+    /// Downward speed while not on the ground
+    pub fall_speed: f32,
+}
+
+/// Killed enemy waiting to be despawned (server-only)
+#[derive(Component)]
+pub struct EnemyCorpse {
+    pub timer: Timer,
 }
 
 /// Client-side marker for rendered enemies
@@ -0,0 +1,381 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::components::{EnemyAwareness, EnemyFlight, EnemyRanged, EnemyState, EnemyTarget};
+use super::lod::AiLod;
+use crate::game::navigation::NavMesh;
+use crate::game::world::level::{CurrentLevel, Level, LevelGeometry, LevelObstacle};
+use crate::network::protocol::{Enemy, Health};
+
+/// Height of a player's eyes above the position enemies remember, cover has to block the view from there
+const PLAYER_EYE_HEIGHT: f32 = 0.6;
+
+/// Where enemies look for cover and how long they hide and peek (server-only)
+#[derive(Resource)]
+pub struct CoverConfig {
+    /// Cover points further away from the enemy aren't considered
+    pub search_radius: f32,
+    /// Seconds between searches while no cover is found
+    pub search_interval: f32,
+    /// Enemies at or below this fraction of max health take cover, ranged enemies always do
+    pub wounded_health: f32,
+    /// Cover closer than this to the target is no cover
+    pub min_target_distance: f32,
+    /// Seconds behind cover before peeking out, and seconds out before hiding again
+    pub hide_time: f32,
+    pub peek_time: f32,
+    /// How far to the side of a cover point an enemy steps out to see its target
+    pub peek_distance: f32,
+    /// Generated points: spacing along obstacle sides, distance from them,
+    /// and the lowest obstacle that hides an enemy
+    pub spacing: f32,
+    pub offset: f32,
+    pub min_height: f32,
+}
+
+impl Default for CoverConfig {
+    fn default() -> Self {
+        Self {
+            search_radius: 15.0,
+            search_interval: 1.0,
+            wounded_health: 0.4,
+            min_target_distance: 3.0,
+            hide_time: 1.5,
+            peek_time: 2.0,
+            peek_distance: 1.2,
+            spacing: 2.0,
+            offset: 0.9,
+            min_height: 1.2,
+        }
+    }
+}
+
+/// Spot next to level geometry an enemy can hide at (server-only)
+#[derive(Clone, Copy)]
+pub struct CoverPoint {
+    pub position: Vec3,
+    /// Enemy using the point, one enemy per point
+    pub occupant: Option<Entity>,
+}
+
+/// Cover points of the level, authored and generated around its obstacles (server-only)
+#[derive(Resource, Default)]
+pub struct CoverPoints {
+    pub points: Vec<CoverPoint>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum CoverPhase {
+    /// On the way to the cover point
+    #[default]
+    Moving,
+    /// Behind cover, out of the target's sight
+    Hidden,
+    /// Stepped out to the side to attack
+    Peeking,
+}
+
+/// Cover an enemy is using (server-only)
+#[derive(Component, Default)]
+pub struct EnemyCover {
+    /// Index into `CoverPoints`, none when the enemy isn't in cover
+    pub point: Option<usize>,
+    pub position: Vec3,
+    pub peek_position: Vec3,
+    pub phase: CoverPhase,
+    pub elapsed: f32,
+    pub since_search: f32,
+}
+
+impl EnemyCover {
+    /// Where the enemy should stand, if it is using cover
+    pub fn goal(&self) -> Option<Vec3> {
+        self.point.map(|_| match self.phase {
+            CoverPhase::Peeking => self.peek_position,
+            CoverPhase::Moving | CoverPhase::Hidden => self.position,
+        })
+    }
+
+    /// Whether the enemy may attack, it holds fire while moving to cover and hiding
+    pub fn exposed(&self) -> bool {
+        self.point.is_none() || self.phase == CoverPhase::Peeking
+    }
+
+    fn leave(&mut self) {
+        self.point = None;
+        self.phase = CoverPhase::Moving;
+        self.elapsed = 0.0;
+    }
+}
+
+/// Collect the authored cover points of the level and generate more along the sides of its obstacles,
+/// wherever the navmesh lets enemies stand (server-only)
+pub fn build_cover_points(
+    mut commands: Commands,
+    config: Res<CoverConfig>,
+    navmesh: Res<NavMesh>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<Level>>,
+) {
+    let Some(level) = current_level.and_then(|current| levels.get(&current.0)) else {
+        return;
+    };
+    let walkable = |position: Vec3| navmesh.cell_of(position).is_some_and(|cell| navmesh.is_walkable(cell));
+
+    let mut positions: Vec<Vec3> = level.cover_points.iter().map(|point| Vec3::from(point.position)).collect();
+    let authored = positions.len();
+    positions.extend(generate_cover_points(&level.obstacles, &config, walkable));
+
+    println!(
+        "[SERVER] {} cover points ({} authored, {} generated)",
+        positions.len(),
+        authored,
+        positions.len() - authored
+    );
+    commands.insert_resource(CoverPoints {
+        points: positions
+            .into_iter()
+            .map(|position| CoverPoint {
+                position,
+                occupant: None,
+            })
+            .collect(),
+    });
+}
+
+/// Points along the sides of obstacles tall enough to hide behind, spaced evenly and kept where `walkable` allows
+fn generate_cover_points(obstacles: &[LevelObstacle], config: &CoverConfig, walkable: impl Fn(Vec3) -> bool) -> Vec<Vec3> {
+    let mut positions = Vec::new();
+    for obstacle in obstacles {
+        let center = Vec3::from(obstacle.center);
+        let half = Vec3::from(obstacle.half_extents);
+        if half.y * 2.0 < config.min_height {
+            continue;
+        }
+        // Spawn points and enemies sit one unit above the floor
+        let height = center.y - half.y + 1.0;
+
+        for (normal, depth, length) in [
+            (Vec3::X, half.x, half.z),
+            (Vec3::NEG_X, half.x, half.z),
+            (Vec3::Z, half.z, half.x),
+            (Vec3::NEG_Z, half.z, half.x),
+        ] {
+            let tangent = Vec3::Y.cross(normal);
+            let count = ((length * 2.0 / config.spacing).floor() as usize).max(1);
+            for index in 0..count {
+                let along = -length + (index as f32 + 0.5) * length * 2.0 / count as f32;
+                let position = (center + normal * (depth + config.offset) + tangent * along).with_y(height);
+                if walkable(position) {
+                    positions.push(position);
+                }
+            }
+        }
+    }
+    positions
+}
+
+/// Ranged and wounded enemies fighting a player move to cover that blocks its line of sight,
+/// peek out to the side to attack and hide again, and find new cover once the target can see them (server-only)
+pub fn enemy_cover_system(
+    config: Res<CoverConfig>,
+    cover_points: Option<ResMut<CoverPoints>>,
+    navmesh: Option<Res<NavMesh>>,
+    mut enemies: Query<
+        (
+            Entity,
+            &Enemy,
+            &Transform,
+            &EnemyState,
+            &Health,
+            &EnemyTarget,
+            &EnemyAwareness,
+            Option<&EnemyRanged>,
+            &AiLod,
+            &mut EnemyCover,
+        ),
+        Without<EnemyFlight>,
+    >,
+    level_geometry: Query<(), With<LevelGeometry>>,
+    rapier_context: ReadRapierContext,
+    time: Res<Time>,
+) {
+    let (Some(mut cover_points), Some(navmesh)) = (cover_points, navmesh) else {
+        return;
+    };
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    // Occupants are marked again below, so points of despawned enemies free up
+    for point in cover_points.points.iter_mut() {
+        point.occupant = None;
+    }
+    for (entity, .., cover) in enemies.iter() {
+        if let Some(point) = cover.point.and_then(|index| cover_points.points.get_mut(index)) {
+            point.occupant = Some(entity);
+        }
+    }
+
+    // Only the level's obstacles give cover, the ground and other fixed colliders don't block the view
+    let is_level_geometry = |entity: Entity| level_geometry.contains(entity);
+    let filter = QueryFilter::only_fixed().predicate(&is_level_geometry);
+    rapier_context.with_query_pipeline(filter, |query_pipeline| {
+        let blocked = |from: Vec3, to: Vec3| {
+            let offset = to - from;
+            let distance = offset.length();
+            distance > 0.01 && query_pipeline.cast_ray(from, offset / distance, distance, true).is_some()
+        };
+
+        for (entity, enemy, transform, state, health, target, awareness, ranged, lod, mut cover) in enemies.iter_mut() {
+            let wounded = health.current <= health.max * config.wounded_health;
+            let fighting = matches!(*state, EnemyState::Chase | EnemyState::Attack) && target.player.is_some();
+            // Enemies only know where they last saw or heard their target
+            let threat = awareness.last_known_position.filter(|_| fighting && (ranged.is_some() || wounded));
+            let Some(threat) = threat else {
+                release(&mut cover_points, &mut cover);
+                continue;
+            };
+            let eye = threat + Vec3::Y * PLAYER_EYE_HEIGHT;
+            let delta = time.delta_secs();
+
+            if cover.point.is_some() {
+                // Flanked, the target can see the cover point
+                if lod.active && !blocked(eye, cover.position) {
+                    release(&mut cover_points, &mut cover);
+                    cover.since_search = config.search_interval;
+                    println!("[SERVER] Enemy {} flanked, leaving cover", enemy.id);
+                } else {
+                    cover.elapsed += delta;
+                    match cover.phase {
+                        CoverPhase::Moving => {
+                            if transform.translation.xz().distance(cover.position.xz()) < 0.6 {
+                                cover.phase = CoverPhase::Hidden;
+                                cover.elapsed = 0.0;
+                            }
+                        }
+                        CoverPhase::Hidden if cover.elapsed >= config.hide_time => {
+                            // Step out on whichever side has a clear view of the target
+                            let side = Vec3::Y.cross((threat - cover.position).with_y(0.0).normalize_or_zero());
+                            let peek = [side, -side]
+                                .into_iter()
+                                .map(|side| cover.position + side * config.peek_distance)
+                                .find(|peek| {
+                                    navmesh.cell_of(*peek).is_some_and(|cell| navmesh.is_walkable(cell))
+                                        && !blocked(eye, *peek)
+                                });
+                            cover.elapsed = 0.0;
+                            if let Some(peek) = peek {
+                                cover.peek_position = peek;
+                                cover.phase = CoverPhase::Peeking;
+                            }
+                        }
+                        CoverPhase::Peeking if cover.elapsed >= config.peek_time => {
+                            cover.phase = CoverPhase::Hidden;
+                            cover.elapsed = 0.0;
+                        }
+                        CoverPhase::Hidden | CoverPhase::Peeking => {}
+                    }
+                    continue;
+                }
+            }
+
+            cover.since_search += delta;
+            if !lod.active || cover.since_search < config.search_interval {
+                continue;
+            }
+            cover.since_search = 0.0;
+
+            // Closest free points first, ranged enemies also prefer their firing distance
+            let position = transform.translation;
+            let mut candidates: Vec<(usize, f32)> = cover_points
+                .points
+                .iter()
+                .enumerate()
+                .filter(|(_, point)| point.occupant.is_none_or(|occupant| occupant == entity))
+                .filter(|(_, point)| {
+                    point.position.distance(position) <= config.search_radius
+                        && point.position.xz().distance(threat.xz()) >= config.min_target_distance
+                })
+                .map(|(index, point)| {
+                    let preferred = ranged.map_or(0.0, |ranged| {
+                        (point.position.xz().distance(threat.xz()) - ranged.preferred_distance).abs()
+                    });
+                    (index, point.position.distance(position) + preferred)
+                })
+                .collect();
+            candidates.sort_by(|a, b| a.1.total_cmp(&b.1));
+
+            let Some(index) = candidates
+                .into_iter()
+                .map(|(index, _)| index)
+                .find(|index| blocked(eye, cover_points.points[*index].position))
+            else {
+                continue;
+            };
+            cover_points.points[index].occupant = Some(entity);
+            cover.point = Some(index);
+            cover.position = cover_points.points[index].position;
+            cover.phase = CoverPhase::Moving;
+            cover.elapsed = 0.0;
+        }
+    });
+}
+
+fn release(cover_points: &mut CoverPoints, cover: &mut EnemyCover) {
+    if let Some(point) = cover.point.and_then(|index| cover_points.points.get_mut(index)) {
+        point.occupant = None;
+    }
+    cover.leave();
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn obstacle(center: (f32, f32, f32), half_extents: (f32, f32, f32)) -> LevelObstacle {
+        LevelObstacle { center, half_extents }
+    }
+
+    fn inside(obstacle: &LevelObstacle, position: Vec3) -> bool {
+        let offset = (position - Vec3::from(obstacle.center)).abs();
+        let half = Vec3::from(obstacle.half_extents);
+        offset.x <= half.x && offset.z <= half.z
+    }
+
+    #[test]
+    fn points_line_every_side_outside_the_obstacle() {
+        let config = CoverConfig::default();
+        // 4 units along x, 2 along z, 2 high, resting on the floor
+        let wall = obstacle((0.0, 1.0, 0.0), (2.0, 1.0, 1.0));
+        let points = generate_cover_points(std::slice::from_ref(&wall), &config, |_| true);
+
+        // Two points on each long side, one on each short side
+        assert_eq!(points.len(), 6);
+        for point in &points {
+            assert!(!inside(&wall, *point));
+            assert_eq!(point.y, 1.0);
+            let offset = (*point - Vec3::from(wall.center)).abs();
+            let gap = (offset.x - 2.0).max(offset.z - 1.0);
+            assert!((gap - config.offset).abs() < 1e-4);
+        }
+    }
+
+    #[test]
+    fn low_obstacles_give_no_cover() {
+        let config = CoverConfig::default();
+        let crate_box = obstacle((5.0, 0.4, 5.0), (1.0, 0.4, 1.0));
+        assert!(generate_cover_points(&[crate_box], &config, |_| true).is_empty());
+    }
+
+    #[test]
+    fn only_walkable_points_are_kept() {
+        let config = CoverConfig::default();
+        let wall = obstacle((0.0, 1.0, 0.0), (2.0, 1.0, 1.0));
+        // Another obstacle blocks the floor on the positive x side
+        let points = generate_cover_points(&[wall], &config, |position| position.x < 2.5);
+
+        assert_eq!(points.len(), 5);
+        assert!(points.iter().all(|point| point.x < 2.5));
+    }
+}
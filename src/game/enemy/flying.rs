@@ -0,0 +1,132 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::components::{
+    get_current_waypoint, AttackPhase, AttackTimer, EnemyAwareness, EnemyBehavior, EnemyFlight, EnemyMovement,
+    EnemyState, EnemyVelocity, FlockingBehavior, PatrolData,
+};
+use super::systems::flee_point;
+use crate::game::spatial::SpatialIndex;
+use crate::network::protocol::Enemy;
+
+/// Flying enemies steer as 3D boids: towards their goal, together with nearby flyers,
+/// between their altitude limits and away from level geometry ahead of them.
+/// They circle above their target at cruise altitude and dive on it to attack (server-only)
+pub fn flying_enemy_movement_system(
+    mut flyers: Query<
+        (
+            Entity,
+            &mut Transform,
+            &EnemyState,
+            &EnemyFlight,
+            &FlockingBehavior,
+            &EnemyMovement,
+            &EnemyBehavior,
+            &EnemyAwareness,
+            &PatrolData,
+            &AttackTimer,
+            &mut EnemyVelocity,
+        ),
+        With<Enemy>,
+    >,
+    flying: Query<(), With<EnemyFlight>>,
+    index: Res<SpatialIndex>,
+    rapier_context: ReadRapierContext,
+    time: Res<Time>,
+) {
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+    let delta = time.delta_secs();
+
+    for (entity, mut transform, state, flight, flock, movement, behavior, awareness, patrol, timer, mut velocity) in
+        flyers.iter_mut()
+    {
+        let position = transform.translation;
+        let cruise = |point: Vec3| point.with_y(flight.cruise_altitude);
+
+        // Dive at the target during the wind-up, pull back up once the strike is done
+        let diving = *state == EnemyState::Attack && timer.phase != AttackPhase::Cooldown;
+        let (goal, speed) = match *state {
+            // Corpses are simulated by physics
+            EnemyState::Dead => continue,
+            EnemyState::Attack if diving => (awareness.last_known_position, flight.dive_speed),
+            EnemyState::Attack | EnemyState::Chase | EnemyState::Search => {
+                (awareness.last_known_position.map(cruise), movement.chase_speed)
+            }
+            EnemyState::Flee => (
+                awareness
+                    .last_known_position
+                    .map(|threat| cruise(flee_point(position, threat, behavior.flee_distance))),
+                movement.chase_speed,
+            ),
+            EnemyState::Patrol => (Some(cruise(get_current_waypoint(patrol))), movement.patrol_speed),
+            // Hover in place with the flock
+            EnemyState::Idle | EnemyState::Stunned => (None, movement.patrol_speed),
+        };
+        let mut desired = goal.map_or(Vec3::ZERO, |goal| (goal - position).normalize_or_zero());
+
+        // Boids in all three dimensions, only with other flyers
+        let mut cohesion = Vec3::ZERO;
+        let mut alignment = Vec3::ZERO;
+        let mut separation = Vec3::ZERO;
+        let mut neighbor_count = 0;
+        for neighbor in index.enemies.within(position, flock.neighbor_range) {
+            if neighbor.entity == entity || !flying.contains(neighbor.entity) {
+                continue;
+            }
+            let offset = neighbor.position - position;
+            let distance = offset.length();
+            if distance < 0.01 {
+                continue;
+            }
+
+            neighbor_count += 1;
+            cohesion += neighbor.position;
+            alignment += neighbor.velocity;
+            if distance < flock.separation_distance {
+                let strength = (flock.separation_distance - distance) / flock.separation_distance;
+                separation -= offset / distance * (strength * strength);
+            }
+        }
+        if neighbor_count > 0 {
+            // A diving flyer commits to its target and only keeps clear of the others
+            if !diving {
+                desired += (cohesion / neighbor_count as f32 - position).normalize_or_zero() * flock.cohesion_weight;
+                desired += (alignment / neighbor_count as f32).normalize_or_zero() * flock.alignment_weight;
+            }
+            desired += separation.normalize_or_zero() * flock.separation_weight;
+        }
+
+        // Climb back up or come back down, diving may go below the minimum altitude
+        if position.y < flight.min_altitude && !diving {
+            desired.y += (flight.min_altitude - position.y) * flight.altitude_weight;
+        } else if position.y > flight.max_altitude {
+            desired.y -= (position.y - flight.max_altitude) * flight.altitude_weight;
+        }
+
+        // Steer away from walls and obstacles in the flight path, harder the closer they are
+        let heading = velocity.velocity.try_normalize().unwrap_or(desired.normalize_or_zero());
+        if heading != Vec3::ZERO {
+            let obstacle = rapier_context.with_query_pipeline(QueryFilter::only_fixed(), |query_pipeline| {
+                query_pipeline.cast_ray_and_get_normal(position, heading, flight.avoid_distance, true)
+            });
+            if let Some((_, intersection)) = obstacle {
+                let closeness = 1.0 - intersection.time_of_impact / flight.avoid_distance;
+                desired += intersection.normal * flight.avoid_weight * closeness;
+            }
+        }
+
+        let desired_velocity = desired.normalize_or_zero() * speed;
+        velocity.velocity = velocity
+            .velocity
+            .lerp(desired_velocity, (flight.turn_rate * delta).min(1.0));
+        transform.translation += velocity.velocity * delta;
+
+        // Face along the flight path, level so the view cone stays usable
+        let facing = velocity.velocity.with_y(0.0);
+        if facing.length_squared() > 0.01 {
+            transform.look_to(facing, Vec3::Y);
+        }
+    }
+}
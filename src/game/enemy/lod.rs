@@ -0,0 +1,218 @@
+use bevy::prelude::*;
+use std::time::{Duration, Instant};
+
+use super::components::EnemyState;
+use crate::game::spatial::SpatialIndex;
+use crate::network::protocol::Enemy;
+
+/// How often enemies think depending on how close the nearest player is (server-only)
+#[derive(Resource)]
+pub struct AiLodConfig {
+    /// Enemies this close to a player think every tick
+    pub near_distance: f32,
+    /// Enemies this close think every `mid_interval`, the rest every `far_interval`
+    pub mid_distance: f32,
+    pub mid_interval: f32,
+    pub far_interval: f32,
+    /// Time per tick for enemy thinking, enemies over budget wait for the next tick.
+    /// Near enemies always think.
+    pub budget: Duration,
+    /// Due enemies deferred this many ticks in a row think anyway,
+    /// so near enemies using up the budget can't starve the others
+    pub max_deferrals: u32,
+}
+
+impl Default for AiLodConfig {
+    fn default() -> Self {
+        Self {
+            near_distance: 20.0,
+            mid_distance: 40.0,
+            mid_interval: 0.1,
+            far_interval: 0.5,
+            budget: Duration::from_millis(4),
+            max_deferrals: 4,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum LodTier {
+    #[default]
+    Near,
+    Mid,
+    Far,
+}
+
+impl LodTier {
+    const ALL: [LodTier; 3] = [LodTier::Near, LodTier::Mid, LodTier::Far];
+
+    fn index(self) -> usize {
+        self as usize
+    }
+}
+
+/// When an enemy last thought (server-only)
+/// Perception, the behavior tree, flocking and repathing only run for enemies marked active this tick,
+/// movement and attacks run every tick so enemies keep moving smoothly.
+#[derive(Component, Default)]
+pub struct AiLod {
+    pub tier: LodTier,
+    /// Whether the enemy thinks this tick
+    pub active: bool,
+    /// Seconds since the previous time it thought, for timers advanced by thinking systems
+    pub delta: f32,
+    since_update: f32,
+    /// Ticks in a row the enemy was due but pushed back by the budget
+    deferrals: u32,
+}
+
+/// Enemy thinking counts per tier (server-only)
+#[derive(Resource, Default)]
+pub struct AiLodMetrics {
+    /// Enemies per tier and how many of them thought, since the last report
+    pub enemies: [u64; 3],
+    pub updated: [u64; 3],
+    /// Due enemies pushed to a later tick by the budget, since the last report
+    pub deferred: u64,
+    pub ticks: u64,
+    pub ai_time: Duration,
+    /// Running estimate of the thinking cost of one enemy, sizes the budget
+    pub cost_per_enemy: Duration,
+    tick_started_at: Option<Instant>,
+    updated_this_tick: usize,
+}
+
+impl AiLodMetrics {
+    /// Summary of the counts since the last reset
+    pub fn summary(&self) -> String {
+        let tiers: Vec<String> = LodTier::ALL
+            .iter()
+            .map(|tier| {
+                let ticks = self.ticks.max(1);
+                format!(
+                    "{:?} {}/{}",
+                    tier,
+                    self.updated[tier.index()] / ticks,
+                    self.enemies[tier.index()] / ticks
+                )
+            })
+            .collect();
+        let average = self.ai_time.as_secs_f64() * 1000.0 / self.ticks.max(1) as f64;
+        format!(
+            "thinking per tick: {}, deferred by budget {}, avg AI time {:.2}ms",
+            tiers.join(", "),
+            self.deferred,
+            average
+        )
+    }
+
+    pub fn reset(&mut self) {
+        self.enemies = [0; 3];
+        self.updated = [0; 3];
+        self.deferred = 0;
+        self.ticks = 0;
+        self.ai_time = Duration::ZERO;
+    }
+}
+
+/// Periodic AI LOD report, inserted when `AI_METRICS` is set (server-only)
+#[derive(Resource)]
+pub struct AiLodReport(pub Timer);
+
+/// Sort enemies into tiers and pick which of them think this tick (server-only).
+/// Due enemies go near tier first, then longest waiting first, until the budget runs out,
+/// so skipped enemies move up the queue, and after `max_deferrals` skips they think regardless of the budget.
+pub fn schedule_enemy_ai(
+    config: Res<AiLodConfig>,
+    mut metrics: ResMut<AiLodMetrics>,
+    mut enemies: Query<(Entity, &Transform, &EnemyState, &mut AiLod), With<Enemy>>,
+    index: Res<SpatialIndex>,
+    time: Res<Time>,
+) {
+    metrics.tick_started_at = Some(Instant::now());
+    metrics.ticks += 1;
+
+    let mut due = Vec::new();
+    for (entity, transform, state, mut lod) in enemies.iter_mut() {
+        lod.active = false;
+        if *state == EnemyState::Dead {
+            continue;
+        }
+        lod.since_update += time.delta_secs();
+
+        let nearest_player = index
+            .players
+            .within(transform.translation, config.mid_distance)
+            .map(|player| player.position.distance(transform.translation))
+            .min_by(|a, b| a.total_cmp(b));
+        lod.tier = match nearest_player {
+            Some(distance) if distance <= config.near_distance => LodTier::Near,
+            Some(_) => LodTier::Mid,
+            None => LodTier::Far,
+        };
+        metrics.enemies[lod.tier.index()] += 1;
+
+        let interval = match lod.tier {
+            LodTier::Near => 0.0,
+            LodTier::Mid => config.mid_interval,
+            LodTier::Far => config.far_interval,
+        };
+        if lod.since_update >= interval {
+            due.push((entity, lod.tier, lod.since_update));
+        }
+    }
+
+    due.sort_by(|a, b| a.1.index().cmp(&b.1.index()).then(b.2.total_cmp(&a.2)));
+    let affordable = if metrics.cost_per_enemy.is_zero() {
+        usize::MAX
+    } else {
+        (config.budget.as_nanos() / metrics.cost_per_enemy.as_nanos()) as usize
+    };
+
+    let mut updated = 0;
+    for (entity, tier, _) in due {
+        let Ok((.., mut lod)) = enemies.get_mut(entity) else {
+            continue;
+        };
+        if tier != LodTier::Near && updated >= affordable && lod.deferrals < config.max_deferrals {
+            lod.deferrals += 1;
+            metrics.deferred += 1;
+            continue;
+        }
+        lod.deferrals = 0;
+        lod.active = true;
+        lod.delta = lod.since_update;
+        lod.since_update = 0.0;
+        metrics.updated[tier.index()] += 1;
+        updated += 1;
+    }
+    metrics.updated_this_tick = updated;
+}
+
+/// Measure how long enemy AI took this tick and update the per-enemy cost estimate (server-only)
+pub fn measure_enemy_ai(mut metrics: ResMut<AiLodMetrics>) {
+    let Some(started_at) = metrics.tick_started_at.take() else {
+        return;
+    };
+    let elapsed = started_at.elapsed();
+    metrics.ai_time += elapsed;
+
+    if metrics.updated_this_tick > 0 {
+        let cost = elapsed / metrics.updated_this_tick as u32;
+        // Smooth over ticks so one slow frame doesn't starve the next ones
+        metrics.cost_per_enemy = if metrics.cost_per_enemy.is_zero() {
+            cost
+        } else {
+            metrics.cost_per_enemy.mul_f32(0.9) + cost.mul_f32(0.1)
+        };
+    }
+}
+
+/// Print the AI LOD metrics periodically (server-only)
+pub fn report_enemy_ai_lod(mut report: ResMut<AiLodReport>, mut metrics: ResMut<AiLodMetrics>, time: Res<Time>) {
+    if !report.0.tick(time.delta()).just_finished() {
+        return;
+    }
+    println!("[SERVER] AI LOD {}", metrics.summary());
+    metrics.reset();
+}
@@ -1,4 +1,18 @@
+pub mod archetype;
+pub mod behavior_tree;
+pub mod boss;
 pub mod components;
+pub mod cover;
+pub mod flying;
+pub mod lod;
+pub mod squad;
 pub mod systems;
 
 pub use systems::*;
+pub use archetype::{load_enemy_archetypes, reload_enemy_archetypes, EnemyArchetype, EnemyArchetypeLoader, EnemyArchetypes};
+pub use behavior_tree::BehaviorTreeDebug;
+pub use boss::{animate_area_attack_warnings, boss_ability_system, boss_phase_system, render_area_attack_warnings, resolve_area_attacks};
+pub use cover::{build_cover_points, enemy_cover_system, CoverConfig, CoverPoints};
+pub use flying::flying_enemy_movement_system;
+pub use lod::{measure_enemy_ai, report_enemy_ai_lod, schedule_enemy_ai, AiLodConfig, AiLodMetrics, AiLodReport};
+pub use squad::{assign_squad_slots, SquadConfig};
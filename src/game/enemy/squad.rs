@@ -0,0 +1,154 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+use std::f32::consts::TAU;
+
+use super::components::{EnemyAwareness, EnemyFlight, EnemyMovement, EnemyState, EnemyStateChanged, EnemyTarget};
+use crate::network::protocol::{Enemy, Player, PlayerRotation};
+
+/// How enemies chasing the same player spread out around it (server-only)
+#[derive(Resource)]
+pub struct SquadConfig {
+    /// Most enemies that get their own slot around one player, the rest chase straight at it
+    pub max_slots: usize,
+    /// Slot distance from the player as a fraction of the enemy's attack range
+    pub slot_distance: f32,
+    /// Seconds between slot reassignments while nobody joins or leaves a squad
+    pub reassign_interval: f32,
+}
+
+impl Default for SquadConfig {
+    fn default() -> Self {
+        Self {
+            max_slots: 8,
+            slot_distance: 0.8,
+            reassign_interval: 0.5,
+        }
+    }
+}
+
+/// Where around its target an enemy attacks from (server-only)
+#[derive(Component, Default)]
+pub struct SquadSlot {
+    /// Player the slot is around, none when the enemy isn't part of a squad
+    pub player: Option<Entity>,
+    /// Slot position relative to the player
+    pub offset: Vec3,
+    /// Slot position this tick
+    pub position: Vec3,
+}
+
+impl SquadSlot {
+    /// Where the enemy should head while chasing, if it has a slot
+    pub fn goal(&self) -> Option<Vec3> {
+        self.player.map(|_| self.position)
+    }
+
+    fn clear(&mut self) {
+        self.player = None;
+    }
+}
+
+/// Give walking enemies chasing the same player in sight distinct slots around it: front, flanks and rear.
+/// Slots are handed out again whenever an enemy changes state, e.g. dies or starts chasing,
+/// and otherwise every `reassign_interval` as enemies move around (server-only)
+pub fn assign_squad_slots(
+    config: Res<SquadConfig>,
+    mut state_changes: MessageReader<EnemyStateChanged>,
+    mut since_reassign: Local<f32>,
+    mut enemies: Query<
+        (Entity, &Transform, &EnemyState, &EnemyTarget, &EnemyAwareness, &EnemyMovement, &mut SquadSlot),
+        (With<Enemy>, Without<EnemyFlight>),
+    >,
+    players: Query<(&Transform, Option<&PlayerRotation>), (With<Player>, Without<Enemy>)>,
+    time: Res<Time>,
+) {
+    *since_reassign += time.delta_secs();
+    let squads_changed = state_changes.read().count() > 0;
+
+    if squads_changed || *since_reassign >= config.reassign_interval {
+        *since_reassign = 0.0;
+        reassign_slots(&config, &mut enemies, &players);
+    }
+
+    // Slots follow their player between reassignments
+    for (.., mut slot) in enemies.iter_mut() {
+        let Some(player) = slot.player else {
+            continue;
+        };
+        match players.get(player) {
+            Ok((player_transform, _)) => slot.position = player_transform.translation + slot.offset,
+            Err(_) => slot.clear(),
+        }
+    }
+}
+
+fn reassign_slots(
+    config: &SquadConfig,
+    enemies: &mut Query<
+        (Entity, &Transform, &EnemyState, &EnemyTarget, &EnemyAwareness, &EnemyMovement, &mut SquadSlot),
+        (With<Enemy>, Without<EnemyFlight>),
+    >,
+    players: &Query<(&Transform, Option<&PlayerRotation>), (With<Player>, Without<Enemy>)>,
+) {
+    // Enemies chasing or attacking a player they can see, grouped by that player
+    let mut squads: HashMap<Entity, Vec<(Entity, Vec3, f32)>> = HashMap::new();
+    for (entity, transform, state, target, awareness, movement, mut slot) in enemies.iter_mut() {
+        slot.clear();
+        if !matches!(*state, EnemyState::Chase | EnemyState::Attack) {
+            continue;
+        }
+        let Some(player) = target.player.filter(|player| awareness.target == Some(*player)) else {
+            continue;
+        };
+        squads
+            .entry(player)
+            .or_default()
+            .push((entity, transform.translation, movement.attack_range * config.slot_distance));
+    }
+
+    for (player, members) in squads {
+        let Ok((player_transform, rotation)) = players.get(player) else {
+            continue;
+        };
+        let center = player_transform.translation;
+
+        // Slot 0 is in front of the player, the others spread evenly around it
+        let front = rotation.map_or(0.0, |rotation| rotation.yaw.to_radians());
+        let slot_count = members.len().min(config.max_slots);
+        let slot_angles: Vec<f32> = (0..slot_count)
+            .map(|index| front + index as f32 * TAU / slot_count as f32)
+            .collect();
+
+        // Greedy matching on bearing keeps enemies in the slot closest to where they already are
+        let mut pairs: Vec<(f32, usize, usize)> = Vec::with_capacity(members.len() * slot_count);
+        for (member_index, (_, position, _)) in members.iter().enumerate() {
+            let offset = *position - center;
+            let bearing = f32::atan2(-offset.x, -offset.z);
+            for (slot_index, angle) in slot_angles.iter().enumerate() {
+                let difference = (bearing - angle).rem_euclid(TAU);
+                pairs.push((difference.min(TAU - difference), member_index, slot_index));
+            }
+        }
+        pairs.sort_by(|a, b| a.0.total_cmp(&b.0));
+
+        let mut member_taken = vec![false; members.len()];
+        let mut slot_taken = vec![false; slot_count];
+        for (_, member_index, slot_index) in pairs {
+            if member_taken[member_index] || slot_taken[slot_index] {
+                continue;
+            }
+            member_taken[member_index] = true;
+            slot_taken[slot_index] = true;
+
+            let (entity, _, distance) = members[member_index];
+            let angle = slot_angles[slot_index];
+            // Same convention as player movement: yaw 0 faces -Z
+            let offset = Vec3::new(-angle.sin(), 0.0, -angle.cos()) * distance;
+            if let Ok((.., mut slot)) = enemies.get_mut(entity) {
+                slot.player = Some(player);
+                slot.offset = offset;
+                slot.position = center + offset;
+            }
+        }
+    }
+}
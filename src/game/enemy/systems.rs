@@ -1,106 +1,264 @@
+use super::archetype::{EnemyArchetypeName, EnemySpawn};
+use super::boss::BossState;
+use super::cover::EnemyCover;
+use super::lod::AiLod;
+use super::squad::SquadSlot;
+use super::behavior_tree::{tick_tree, BehaviorContext, BehaviorTreeDebug, Blackboard, EnemyBehaviorTree};
 use super::components::{
-    EnemyMovement, EnemyState, EnemyVelocity, FlockingBehavior, PatrolData, RenderedEnemy,
-    advance_waypoint, get_current_waypoint,
+    AttackPhase, AttackTimer, EnemyAttack, EnemyAwareness, EnemyBehavior, EnemyCorpse, EnemyMovement, EnemyPerception,
+    EnemyFlight, EnemyRanged, EnemyState, EnemyStateChanged, EnemyStateTimer, EnemyStrafe, EnemyTarget, EnemyThreat, EnemyVelocity, FlockingBehavior, Gunshot,
+    PatrolData, RenderedEnemy, StunBuildup, ThreatTable, get_current_waypoint,
 };
-use crate::network::protocol::{Enemy, EnemyPosition, PlayerPosition};
+use crate::game::navigation::NavPath;
+use crate::game::projectile::{spawn_projectile, ProjectileFlight};
+use crate::game::spatial::SpatialIndex;
+use crate::game::player::{damage_player, systems::NeedsRespawn};
+use crate::network::protocol::{Boss, Enemy, EnemyAppearance, EnemyPosition, Health, Player, PlayerPosition, PlayerStats};
 use bevy::prelude::*;
 use bevy_rapier3d::prelude::*;
 use bevy_replicon::prelude::*;
 
-/// Spawn enemies in the world (server-side)
-pub fn spawn_enemies_system(mut commands: Commands, enemies: Query<&Enemy>) {
-    // Spawn 3 enemies in a cluster
-    if enemies.iter().count() > 0 {
-        return;
+/// Seconds a dead enemy stays around as a ragdoll before it is despawned
+const ENEMY_CORPSE_SECS: f32 = 3.0;
+
+/// Steepest slope enemies walk up, matches what the navmesh treats as walkable ground
+const ENEMY_MAX_SLOPE_DEGREES: f32 = 45.0;
+/// Highest ledge enemies step onto without a ramp, same as `NavMeshConfig::step_height`
+const ENEMY_STEP_HEIGHT: f32 = 0.3;
+/// How far below its feet an enemy looks for ground to stick to when walking down slopes and steps
+const ENEMY_GROUND_SNAP: f32 = 0.4;
+const ENEMY_GRAVITY: f32 = 20.0;
+
+/// Character controller moving enemies through the physics world instead of through walls (server-side)
+/// It slides along level geometry, players and other enemies, so enemies can't overlap.
+fn enemy_character_controller() -> KinematicCharacterController {
+    KinematicCharacterController {
+        offset: CharacterLength::Absolute(0.05),
+        up: Vec3::Y,
+        slide: true,
+        max_slope_climb_angle: ENEMY_MAX_SLOPE_DEGREES.to_radians(),
+        min_slope_slide_angle: ENEMY_MAX_SLOPE_DEGREES.to_radians(),
+        autostep: Some(CharacterAutostep {
+            max_height: CharacterLength::Absolute(ENEMY_STEP_HEIGHT),
+            min_width: CharacterLength::Absolute(0.2),
+            include_dynamic_bodies: false,
+        }),
+        snap_to_ground: Some(CharacterLength::Absolute(ENEMY_GROUND_SNAP)),
+        // Enemies don't shove players around, they stop against them
+        apply_impulse_to_dynamic_bodies: false,
+        ..default()
     }
+}
 
-    // Spawn 3 enemies in a triangle formation to demonstrate flocking
-    let cluster_center = Vec3::new(10.0, 1.0, 10.0);
-    let spawn_radius = 3.0;
-
-    // Enemy 1 - north
-    let spawn_pos_1 = cluster_center + Vec3::new(0.0, 0.0, spawn_radius);
-    commands.spawn((
-        Enemy { id: 1 },
-        EnemyPosition {
-            x: spawn_pos_1.x,
-            y: spawn_pos_1.y,
-            z: spawn_pos_1.z,
-        },
-        EnemyState::Patrol,
-        PatrolData::new(cluster_center, 5.0),
-        EnemyMovement::default(),
-        FlockingBehavior::default(),
-        EnemyVelocity::default(),
-        Transform::from_translation(spawn_pos_1),
-        GlobalTransform::default(),
-        Collider::capsule_y(0.5, 0.5),
-        RigidBody::KinematicPositionBased,
-        Replicated,
-    ));
+/// Spawn a single enemy walking the given patrol (server-side)
+pub fn spawn_enemy(commands: &mut Commands, id: u32, spawn: &EnemySpawn, position: Vec3, patrol: PatrolData) -> Entity {
+    // Spawn points are at the center height of a 2 unit tall enemy, lift bigger ones out of the floor
+    let lift = (spawn.appearance.half_height + spawn.appearance.radius - 1.0).max(0.0);
+    let position = position + Vec3::Y * lift;
+    let entity = commands
+        .spawn((
+            Enemy { id },
+            EnemyPosition {
+                x: position.x,
+                y: position.y,
+                z: position.z,
+            },
+            Health {
+                current: spawn.max_health,
+                max: spawn.max_health,
+            },
+            spawn.appearance.clone(),
+            // AI state
+            (
+                EnemyState::Patrol,
+                EnemyStateTimer::default(),
+                patrol,
+                AttackTimer::default(),
+                EnemyAwareness::default(),
+                ThreatTable::default(),
+                EnemyTarget::default(),
+                SquadSlot::default(),
+                StunBuildup::default(),
+                AiLod::default(),
+                EnemyVelocity::default(),
+                NavPath::default(),
+                EnemyBehaviorTree(spawn.tree.clone()),
+                Blackboard::default(),
+            ),
+            // Tuning from the archetype
+            (
+                EnemyArchetypeName(spawn.archetype.clone()),
+                spawn.scaling,
+                spawn.movement.clone(),
+                spawn.attack.clone(),
+                spawn.flocking.clone(),
+                spawn.perception.clone(),
+                spawn.behavior.clone(),
+                spawn.threat.clone(),
+            ),
+            Transform::from_translation(position),
+            GlobalTransform::default(),
+            spawn.collider(),
+            RigidBody::KinematicPositionBased,
+            Replicated,
+        ))
+        .id();
+
+    if let Some(ranged) = &spawn.ranged {
+        commands.entity(entity).insert((ranged.clone(), EnemyStrafe::default()));
+    }
+    if let Some(boss) = &spawn.boss {
+        commands.entity(entity).insert((
+            boss.clone(),
+            BossState::new(spawn),
+            Boss {
+                name: spawn.archetype.clone(),
+                phase: 0,
+            },
+        ));
+    }
+    // Flyers steer themselves through the air, everyone else walks with the character controller
+    match &spawn.flight {
+        Some(flight) => commands.entity(entity).insert(flight.clone()),
+        None => commands.entity(entity).insert((enemy_character_controller(), EnemyCover::default())),
+    };
+    entity
+}
 
-    // Enemy 2 - southwest
-    let spawn_pos_2 = cluster_center + Vec3::new(-spawn_radius * 0.866, 0.0, -spawn_radius * 0.5);
-    commands.spawn((
-        Enemy { id: 2 },
-        EnemyPosition {
-            x: spawn_pos_2.x,
-            y: spawn_pos_2.y,
-            z: spawn_pos_2.z,
+/// Turn a killed enemy into a ragdoll that falls over from the shot (server-side)
+pub fn kill_enemy(commands: &mut Commands, entity: Entity, state: &mut EnemyState, shot_direction: Vec3) {
+    *state = EnemyState::Dead;
+    commands.entity(entity).remove::<KinematicCharacterController>().insert((
+        EnemyCorpse {
+            timer: Timer::from_seconds(ENEMY_CORPSE_SECS, TimerMode::Once),
         },
-        EnemyState::Patrol,
-        PatrolData::new(cluster_center, 5.0),
-        EnemyMovement::default(),
-        FlockingBehavior::default(),
-        EnemyVelocity::default(),
-        Transform::from_translation(spawn_pos_2),
-        GlobalTransform::default(),
-        Collider::capsule_y(0.5, 0.5),
-        RigidBody::KinematicPositionBased,
-        Replicated,
-    ));
-
-    // Enemy 3 - southeast
-    let spawn_pos_3 = cluster_center + Vec3::new(spawn_radius * 0.866, 0.0, -spawn_radius * 0.5);
-    commands.spawn((
-        Enemy { id: 3 },
-        EnemyPosition {
-            x: spawn_pos_3.x,
-            y: spawn_pos_3.y,
-            z: spawn_pos_3.z,
+        RigidBody::Dynamic,
+        ExternalImpulse {
+            impulse: shot_direction.normalize_or_zero() * 5.0,
+            torque_impulse: Vec3::ZERO,
         },
-        EnemyState::Patrol,
-        PatrolData::new(cluster_center, 5.0),
-        EnemyMovement::default(),
-        FlockingBehavior::default(),
-        EnemyVelocity::default(),
-        Transform::from_translation(spawn_pos_3),
-        GlobalTransform::default(),
-        Collider::capsule_y(0.5, 0.5),
-        RigidBody::KinematicPositionBased,
-        Replicated,
     ));
 }
 
-/// Client-side rendering for enemies (add visual mesh)
+/// Despawn dead enemies once their corpse timer runs out (server-side)
+pub fn despawn_dead_enemies(
+    mut commands: Commands,
+    mut corpses: Query<(Entity, &Enemy, &mut EnemyCorpse)>,
+    time: Res<Time>,
+) {
+    for (entity, enemy, mut corpse) in corpses.iter_mut() {
+        corpse.timer.tick(time.delta());
+        if corpse.timer.is_finished() {
+            commands.entity(entity).despawn();
+            println!("[SERVER] Enemy {} corpse removed", enemy.id);
+        }
+    }
+}
+
+/// Client-side rendering for enemies (add visual mesh or model from the replicated appearance)
 pub fn render_enemies_system(
     mut commands: Commands,
     mut meshes: ResMut<Assets<Mesh>>,
     mut materials: ResMut<Assets<StandardMaterial>>,
-    enemies: Query<Entity, (With<Enemy>, Without<RenderedEnemy>)>,
+    asset_server: Res<AssetServer>,
+    enemies: Query<(Entity, &EnemyAppearance), (With<Enemy>, Without<RenderedEnemy>)>,
 ) {
-    for entity in enemies.iter() {
+    for (entity, appearance) in enemies.iter() {
         commands.entity(entity).insert((
             RenderedEnemy,
-            Mesh3d(meshes.add(Capsule3d::new(0.5, 1.0))),
-            MeshMaterial3d(materials.add(StandardMaterial {
-                base_color: Color::srgb(1.0, 0.0, 0.0),
-                ..default()
-            })),
             Visibility::default(),
             InheritedVisibility::default(),
             ViewVisibility::default(),
         ));
+
+        if let Some(model) = &appearance.model {
+            let model_entity = commands
+                .spawn((
+                    SceneRoot(asset_server.load(model.clone())),
+                    // Model origin is at the feet, the enemy origin is the capsule center
+                    Transform::from_xyz(0.0, -(appearance.half_height + appearance.radius), 0.0),
+                    GlobalTransform::default(),
+                ))
+                .id();
+            commands.entity(model_entity).set_parent_in_place(entity);
+        } else {
+            let [r, g, b] = appearance.color;
+            commands.entity(entity).insert((
+                Mesh3d(meshes.add(Capsule3d::new(appearance.radius, appearance.half_height * 2.0))),
+                MeshMaterial3d(materials.add(StandardMaterial {
+                    base_color: Color::srgb(r, g, b),
+                    ..default()
+                })),
+            ));
+        }
+    }
+}
+
+/// Drop the visuals of enemies whose archetype was hot reloaded so `render_enemies_system` builds them
+/// again from the new model, colour and size (client-side)
+pub fn refresh_enemy_appearance(
+    mut commands: Commands,
+    enemies: Query<(Entity, Ref<RenderedEnemy>, Option<&Children>), (With<Enemy>, Changed<EnemyAppearance>)>,
+    models: Query<(), With<SceneRoot>>,
+) {
+    for (entity, rendered, children) in enemies.iter() {
+        // Just rendered from this appearance
+        if rendered.is_added() {
+            continue;
+        }
+        let children: &[Entity] = children.map(|children| &**children).unwrap_or_default();
+        for &child in children {
+            if models.contains(child) {
+                commands.entity(child).despawn();
+            }
+        }
+        commands
+            .entity(entity)
+            .remove::<(RenderedEnemy, Mesh3d, MeshMaterial3d<StandardMaterial>)>();
+    }
+}
+
+/// Tint living enemies by their replicated state so players can read what they're doing (client-side)
+pub fn render_enemy_states(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    enemies: Query<
+        (&EnemyState, &MeshMaterial3d<StandardMaterial>),
+        (With<RenderedEnemy>, Or<(Changed<EnemyState>, Added<RenderedEnemy>)>),
+    >,
+) {
+    for (state, material) in enemies.iter() {
+        let Some(material) = materials.get_mut(&material.0) else {
+            continue;
+        };
+
+        material.emissive = match state {
+            EnemyState::Attack => LinearRgba::rgb(0.8, 0.0, 0.0),
+            EnemyState::Search => LinearRgba::rgb(0.5, 0.3, 0.0),
+            EnemyState::Flee => LinearRgba::rgb(0.0, 0.2, 0.8),
+            EnemyState::Stunned => LinearRgba::rgb(0.8, 0.8, 0.2),
+            _ => LinearRgba::BLACK,
+        };
+    }
+}
+
+/// Show killed enemies lying on the ground in a dark colour (client-side)
+pub fn render_dead_enemies(
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    mut enemies: Query<
+        (&Health, Option<&MeshMaterial3d<StandardMaterial>>, &mut Transform),
+        (With<RenderedEnemy>, Or<(Changed<Health>, Added<RenderedEnemy>)>),
+    >,
+) {
+    for (health, material, mut transform) in enemies.iter_mut() {
+        if health.current > 0.0 {
+            continue;
+        }
+
+        // Only capsules have a material to darken, models just fall over
+        if let Some(material) = material.and_then(|material| materials.get_mut(&material.0)) {
+            material.base_color = Color::srgb(0.2, 0.05, 0.05);
+        }
+        transform.rotation = Quat::from_rotation_x(std::f32::consts::FRAC_PI_2);
     }
 }
 
@@ -115,61 +273,286 @@ pub fn sync_enemy_position(
     }
 }
 
-/// FSM state transition system (server-only)
-pub fn enemy_fsm_system(
-    mut enemies: Query<(&Enemy, &Transform, &mut EnemyState, &EnemyMovement), With<Enemy>>,
-    players: Query<&Transform, With<PlayerPosition>>,
+/// Notice players in the view cone that aren't hidden behind level geometry,
+/// and gunshots within hearing range, then pick the target from the threat table (server-only)
+pub fn enemy_perception_system(
+    mut gunshots: MessageReader<Gunshot>,
+    mut enemies: Query<
+        (
+            &Transform,
+            &EnemyState,
+            &EnemyPerception,
+            &EnemyThreat,
+            &mut ThreatTable,
+            &mut EnemyTarget,
+            &mut EnemyAwareness,
+            &AiLod,
+        ),
+        With<Enemy>,
+    >,
+    players: Query<&Health, (With<Player>, Without<NeedsRespawn>, Without<Enemy>)>,
+    index: Res<SpatialIndex>,
+    rapier_context: ReadRapierContext,
 ) {
-    for (enemy, enemy_transform, mut state, movement) in enemies.iter_mut() {
-        // Find closest player
-        let closest_player_dist = players
+    let shots: Vec<Gunshot> = gunshots.read().copied().collect();
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    for (transform, state, perception, threat, mut threat_table, mut target, mut awareness, lod) in enemies.iter_mut() {
+        if *state == EnemyState::Dead {
+            awareness.forget();
+            threat_table.clear();
+            target.player = None;
+            continue;
+        }
+
+        // Gunshots only happen once, enemies that don't think this tick still hear them
+        let heard = shots
             .iter()
-            .map(|player_transform| {
-                enemy_transform
-                    .translation
-                    .distance(player_transform.translation)
-            })
-            .min_by(|a, b| a.partial_cmp(b).unwrap());
-
-        let Some(dist) = closest_player_dist else {
-            // No players, patrol
-            if *state != EnemyState::Patrol {
-                *state = EnemyState::Patrol;
-                println!("[SERVER] Enemy {} -> PATROL", enemy.id);
+            .filter(|shot| shot.origin.distance(transform.translation) <= perception.hearing_radius)
+            .min_by(|a, b| {
+                a.origin
+                    .distance(transform.translation)
+                    .total_cmp(&b.origin.distance(transform.translation))
+            });
+        if !lod.active {
+            if let Some(shot) = heard.filter(|_| awareness.target.is_none()) {
+                awareness.last_known_position = Some(shot.origin);
+                awareness.since_noticed = 0.0;
             }
             continue;
+        }
+        let delta = lod.delta;
+
+        let eye = transform.translation + Vec3::Y * perception.eye_height;
+        let forward = flat_direction(Vec3::ZERO, *transform.forward());
+        let min_cos = (perception.field_of_view.to_radians() * 0.5).cos();
+
+        // Only static level geometry blocks the view, other enemies don't
+        let seen: Vec<(Entity, Vec3, f32)> = rapier_context.with_query_pipeline(QueryFilter::only_fixed(), |query_pipeline| {
+            index
+                .players
+                .within(eye, perception.view_distance)
+                .filter_map(|candidate| {
+                    let offset = candidate.position - eye;
+                    let distance = offset.length();
+                    if distance > perception.view_distance || distance < 0.01 {
+                        return None;
+                    }
+
+                    let in_cone = flat_direction(eye, candidate.position).dot(forward) >= min_cos;
+                    if !in_cone && distance > perception.awareness_radius {
+                        return None;
+                    }
+
+                    let occluded = query_pipeline.cast_ray(eye, offset / distance, distance, true).is_some();
+                    (!occluded).then_some((candidate.entity, candidate.position, distance))
+                })
+                .collect()
+        });
+
+        // Players in sight build up threat, the closer the faster
+        for (player, _, distance) in &seen {
+            let closeness = 1.0 - distance / perception.view_distance;
+            threat_table.add(*player, threat.proximity_threat * closeness * delta);
+        }
+        update_target(&mut threat_table, &mut target, threat, &seen, &players, delta);
+
+        let target_seen = target
+            .player
+            .and_then(|player| seen.iter().find(|(entity, ..)| *entity == player));
+        if let Some((entity, position, _)) = target_seen {
+            awareness.target = Some(*entity);
+            awareness.last_known_position = Some(*position);
+            awareness.since_noticed = 0.0;
+            continue;
+        }
+        awareness.target = None;
+
+        // Gunshots give away the shooter's position even through walls
+        if let Some(shot) = heard {
+            awareness.last_known_position = Some(shot.origin);
+            awareness.since_noticed = 0.0;
+            continue;
+        }
+
+        awareness.since_noticed += delta;
+    }
+}
+
+// Decay threat, drop players that died, left or started spectating, and switch targets
+// only when someone clearly out-threatens the current one
+fn update_target(
+    threat_table: &mut ThreatTable,
+    target: &mut EnemyTarget,
+    threat: &EnemyThreat,
+    seen: &[(Entity, Vec3, f32)],
+    players: &Query<&Health, (With<Player>, Without<NeedsRespawn>, Without<Enemy>)>,
+    delta: f32,
+) {
+    let decay = (1.0 - threat.decay * delta).max(0.0);
+    threat_table.threat.retain(|player, amount| {
+        *amount *= decay;
+        *amount > 0.01 && players.get(*player).is_ok_and(|health| health.current > 0.0)
+    });
+
+    target.since_switch += delta;
+    if target.player.is_some_and(|player| !threat_table.threat.contains_key(&player)) {
+        target.player = None;
+    }
+
+    let Some((best, best_threat)) = threat_table.highest() else {
+        return;
+    };
+    let switch = match target.player {
+        None => true,
+        Some(current) if current == best => false,
+        Some(current) => {
+            // A target out of sight holds on by threat alone, without the margin
+            let in_sight = seen.iter().any(|(entity, ..)| *entity == current);
+            let margin = if in_sight { threat.switch_margin } else { 1.0 };
+            target.since_switch >= threat.switch_cooldown && best_threat > threat_table.get(current) * margin
+        }
+    };
+    if switch {
+        target.player = Some(best);
+        target.since_switch = 0.0;
+    }
+}
+
+/// Tick every enemy's behavior tree, its actions pick the state the other systems carry out (server-only)
+pub fn enemy_behavior_tree_system(
+    mut enemies: Query<
+        (
+            &Transform,
+            &Health,
+            &mut EnemyState,
+            &mut EnemyStateTimer,
+            &EnemyBehaviorTree,
+            &mut Blackboard,
+            (&EnemyMovement, &EnemyPerception, &EnemyBehavior),
+            &mut EnemyAwareness,
+            &mut PatrolData,
+            &mut NavPath,
+            &AiLod,
+        ),
+        With<Enemy>,
+    >,
+    players: Query<&Transform, (With<PlayerPosition>, Without<Enemy>)>,
+    time: Res<Time>,
+) {
+    for (
+        enemy_transform,
+        health,
+        mut state,
+        mut timer,
+        tree,
+        mut blackboard,
+        (movement, perception, behavior),
+        mut awareness,
+        mut patrol,
+        mut path,
+        lod,
+    ) in enemies.iter_mut()
+    {
+        if *state == EnemyState::Dead || !lod.active {
+            continue;
+        }
+        timer.elapsed += lod.delta;
+        let position = enemy_transform.translation;
+
+        // Distance to the player in sight, if any
+        let target_distance = awareness
+            .target
+            .and_then(|target| players.get(target).ok())
+            .map(|player_transform| position.distance(player_transform.translation));
+
+        let mut context = BehaviorContext {
+            position,
+            health,
+            state: *state,
+            next_state: *state,
+            timer: &timer,
+            movement,
+            perception,
+            behavior,
+            awareness: &mut awareness,
+            patrol: &mut patrol,
+            path: &mut path,
+            target_distance,
+            now: time.elapsed_secs(),
         };
+        tick_tree(&tree.0, &mut blackboard, &mut context);
 
-        // State transitions based on distance
-        match *state {
-            EnemyState::Patrol => {
-                if dist <= movement.chase_range {
-                    *state = EnemyState::Chase;
-                    println!("[SERVER] Enemy {} -> CHASE", enemy.id);
-                }
-            }
-            EnemyState::Chase => {
-                if dist <= movement.attack_range {
-                    *state = EnemyState::Attack;
-                    println!("[SERVER] Enemy {} -> ATTACK", enemy.id);
-                } else if dist > movement.chase_range {
-                    *state = EnemyState::Patrol;
-                    println!("[SERVER] Enemy {} -> PATROL", enemy.id);
-                }
-            }
-            EnemyState::Attack => {
-                if dist > movement.attack_range && dist <= movement.chase_range {
-                    *state = EnemyState::Chase;
-                    println!("[SERVER] Enemy {} -> CHASE", enemy.id);
-                } else if dist > movement.chase_range {
-                    *state = EnemyState::Patrol;
-                    println!("[SERVER] Enemy {} -> PATROL", enemy.id);
-                }
-            }
+        let next_state = context.next_state;
+        if next_state != *state {
+            *state = next_state;
+        }
+    }
+}
+
+/// Dump which node of its tree every enemy is running (server-only)
+pub fn dump_behavior_trees(
+    mut debug: ResMut<BehaviorTreeDebug>,
+    enemies: Query<(&Enemy, &EnemyArchetypeName, &EnemyState, &Blackboard)>,
+    time: Res<Time>,
+) {
+    if !debug.0.tick(time.delta()).just_finished() {
+        return;
+    }
+
+    let mut enemies: Vec<_> = enemies.iter().collect();
+    enemies.sort_by_key(|(enemy, ..)| enemy.id);
+    for (enemy, archetype, state, blackboard) in enemies {
+        println!("[BT] Enemy {} ({}, {:?}): {}", enemy.id, archetype.0, state, blackboard.active);
+    }
+}
+
+/// Reset state timers and paths and announce state changes (server-only)
+pub fn track_enemy_state_changes(
+    mut enemies: Query<
+        (Entity, &Enemy, &EnemyState, &mut EnemyStateTimer, &mut NavPath, &mut PatrolData),
+        Changed<EnemyState>,
+    >,
+    mut state_changes: MessageWriter<EnemyStateChanged>,
+) {
+    for (entity, enemy, state, mut timer, mut path, mut patrol) in enemies.iter_mut() {
+        if *state == timer.previous {
+            continue;
+        }
+
+        state_changes.write(EnemyStateChanged {
+            entity,
+            enemy_id: enemy.id,
+            from: timer.previous,
+            to: *state,
+        });
+        timer.previous = *state;
+        timer.elapsed = 0.0;
+        path.clear();
+        // Enemies leaving their route to fight pick it up again at the nearest waypoint
+        if matches!(*state, EnemyState::Chase | EnemyState::Attack | EnemyState::Search | EnemyState::Flee) {
+            patrol.interrupted = true;
         }
     }
 }
 
+/// Print enemy state changes to the server log (server-only)
+pub fn log_enemy_state_changes(mut state_changes: MessageReader<EnemyStateChanged>) {
+    for change in state_changes.read() {
+        let name = format!("{:?}", change.to).to_uppercase();
+        println!("[SERVER] Enemy {} -> {}", change.enemy_id, name);
+    }
+}
+
+/// Knock an enemy out of whatever it was doing for a moment (server-side)
+pub fn stun_enemy(state: &mut EnemyState) {
+    if *state != EnemyState::Dead {
+        *state = EnemyState::Stunned;
+    }
+}
+
 /// Flocking behavior system - calculates flocking forces (server-only)
 /// This runs before movement to calculate desired velocities based on neighbors
 pub fn enemy_flocking_system(
@@ -179,23 +562,20 @@ pub fn enemy_flocking_system(
             &Transform,
             &EnemyState,
             &FlockingBehavior,
+            &SquadSlot,
+            &AiLod,
             &mut EnemyVelocity,
         ),
-        With<Enemy>,
+        (With<Enemy>, Without<EnemyFlight>),
     >,
+    flyers: Query<(), With<EnemyFlight>>,
+    index: Res<SpatialIndex>,
 ) {
-    // Collect all enemy positions and velocities for neighbor calculations
-    let enemy_data: Vec<(Entity, Vec3, Vec3)> = enemies
-        .iter()
-        .map(|(entity, transform, _state, _flock, velocity)| {
-            (entity, transform.translation, velocity.velocity)
-        })
-        .collect();
-
     // Calculate flocking forces for each enemy
-    for (entity, transform, state, flock_params, mut velocity) in enemies.iter_mut() {
-        // Only apply flocking when in Chase state (cooperative hunting)
-        if *state != EnemyState::Chase {
+    for (entity, transform, state, flock_params, slot, lod, mut velocity) in enemies.iter_mut() {
+        // Only apply flocking when in Chase state (cooperative hunting),
+        // enemies that don't think this tick keep their last flocking force
+        if *state != EnemyState::Chase || !lod.active {
             continue;
         }
 
@@ -206,13 +586,13 @@ pub fn enemy_flocking_system(
 
         let my_position = transform.translation;
 
-        // Calculate flocking forces based on neighbors
-        for (other_entity, other_position, other_velocity) in &enemy_data {
-            if *other_entity == entity {
-                continue; // Skip self
+        // Calculate flocking forces based on neighbors, the index only returns nearby enemies
+        for neighbor in index.enemies.within(my_position, flock_params.neighbor_range) {
+            if neighbor.entity == entity || flyers.contains(neighbor.entity) {
+                continue; // Skip self, flyers flock among themselves
             }
 
-            let offset = *other_position - my_position;
+            let offset = neighbor.position - my_position;
             let distance = offset.length();
 
             // Only consider enemies within neighbor range
@@ -220,10 +600,10 @@ pub fn enemy_flocking_system(
                 neighbor_count += 1;
 
                 // Cohesion: steer towards average position of neighbors
-                cohesion += *other_position;
+                cohesion += neighbor.position;
 
                 // Alignment: steer towards average velocity of neighbors
-                alignment += *other_velocity;
+                alignment += neighbor.velocity;
 
                 // Separation: steer away from neighbors that are too close
                 // Use inverse square law - stronger repulsion when very close
@@ -249,6 +629,12 @@ pub fn enemy_flocking_system(
             // Separation: already calculated, just apply weight
             separation = separation.normalize_or_zero() * flock_params.separation_weight;
 
+            // Enemies with a squad slot only keep apart, cohesion and alignment would pull them back into a line
+            if slot.player.is_some() {
+                cohesion = Vec3::ZERO;
+                alignment = Vec3::ZERO;
+            }
+
             // Combine all forces
             let flocking_force = cohesion + alignment + separation;
 
@@ -262,82 +648,293 @@ pub fn enemy_flocking_system(
 }
 
 /// Enemy movement system based on current state (server-only)
+/// Enemies follow their navmesh path and walk straight at the target when there is none.
+/// Movement goes through the character controller, which applies it during the physics step.
 pub fn enemy_movement_system(
     mut enemies: Query<
         (
             &mut Transform,
+            &mut KinematicCharacterController,
+            Option<&KinematicCharacterControllerOutput>,
             &EnemyState,
-            &mut PatrolData,
+            &PatrolData,
             &EnemyMovement,
-            &EnemyVelocity,
+            &EnemyBehavior,
+            &mut EnemyVelocity,
+            &EnemyAwareness,
+            &SquadSlot,
+            &EnemyCover,
+            &NavPath,
+            Option<(&EnemyRanged, &mut EnemyStrafe)>,
         ),
         With<Enemy>,
     >,
-    players: Query<&Transform, (With<PlayerPosition>, Without<Enemy>)>,
     time: Res<Time>,
 ) {
-    for (mut enemy_transform, state, mut patrol, movement, velocity) in enemies.iter_mut() {
+    for (
+        mut enemy_transform,
+        mut controller,
+        controller_output,
+        state,
+        patrol,
+        movement,
+        behavior,
+        mut velocity,
+        awareness,
+        slot,
+        cover,
+        path,
+        mut ranged,
+    ) in enemies.iter_mut()
+    {
+        let mut step = Vec3::ZERO;
         match *state {
+            // Corpses are simulated by physics
+            EnemyState::Dead => continue,
+            // Standing still, waiting for the timer or the stun to run out
+            EnemyState::Idle | EnemyState::Stunned => {}
             EnemyState::Patrol => {
-                let waypoint = get_current_waypoint(&patrol);
-                let direction = (waypoint - enemy_transform.translation).normalize_or_zero();
-
-                if enemy_transform.translation.distance(waypoint) < 0.5 {
-                    advance_waypoint(&mut patrol);
+                let waypoint = get_current_waypoint(patrol);
+                let target = path.next_waypoint().unwrap_or(waypoint);
+                let direction = flat_direction(enemy_transform.translation, target);
+
+                step = direction * movement.patrol_speed * time.delta_secs();
+                // Look where we walk so the view cone sweeps the route
+                if direction.length_squared() > 0.0 {
+                    enemy_transform.look_to(direction, Vec3::Y);
                 }
-
-                enemy_transform.translation +=
-                    direction * movement.patrol_speed * time.delta_secs();
             }
             EnemyState::Chase | EnemyState::Attack => {
-                // Find closest player
-                if let Some(player_transform) = players.iter().min_by_key(|player_transform| {
-                    (enemy_transform
-                        .translation
-                        .distance(player_transform.translation)
-                        * 100.0) as i32
-                }) {
-                    let direction = (player_transform.translation - enemy_transform.translation)
-                        .normalize_or_zero();
-
-                    let speed = if *state == EnemyState::Chase {
-                        movement.chase_speed
-                    } else {
-                        0.0 // Stand still when attacking
+                if let Some(last_known) = awareness.last_known_position {
+                    // Head for cover or our slot around the target, or straight at it without either
+                    let cover_goal = cover.goal();
+                    let target = path
+                        .next_waypoint()
+                        .or(cover_goal)
+                        .or(slot.goal())
+                        .unwrap_or(last_known);
+                    let direction = flat_direction(enemy_transform.translation, target);
+
+                    let speed = match cover_goal {
+                        // Hold still once behind cover or out peeking
+                        Some(goal) if enemy_transform.translation.xz().distance(goal.xz()) < 0.3 => 0.0,
+                        Some(_) => movement.chase_speed,
+                        None if *state == EnemyState::Chase => movement.chase_speed,
+                        None => 0.0, // Melee enemies stand still when attacking
                     };
 
-                    // Combine player-seeking direction with flocking velocity
+                    // Combine path-following direction with flocking velocity
                     let mut final_direction = direction;
                     if velocity.velocity.length_squared() > 0.01 {
-                        // Blend flocking (30%) with player-seeking (70%)
+                        // Blend flocking (30%) with path-following (70%)
                         final_direction = (direction * 0.7
-                            + velocity.velocity.normalize_or_zero() * 0.3)
+                            + velocity.velocity.with_y(0.0).normalize_or_zero() * 0.3)
                             .normalize_or_zero();
                     }
 
-                    enemy_transform.translation += final_direction * speed * time.delta_secs();
+                    step = final_direction * speed * time.delta_secs();
+
+                    // Ranged enemies out of cover hold their distance and strafe while firing
+                    if let (EnemyState::Attack, Some((ranged, strafe)), None) = (*state, ranged.as_mut(), cover_goal) {
+                        strafe.elapsed += time.delta_secs();
+                        if strafe.elapsed >= ranged.strafe_switch_time {
+                            strafe.elapsed = 0.0;
+                            strafe.direction = -strafe.direction;
+                        }
+                        let toward = flat_direction(enemy_transform.translation, last_known);
+                        let distance = enemy_transform.translation.xz().distance(last_known.xz());
+                        let radial = (distance - ranged.preferred_distance).clamp(-1.0, 1.0);
+                        let sideways = Vec3::Y.cross(toward) * strafe.direction;
+                        step = (toward * radial + sideways).normalize_or_zero() * ranged.strafe_speed * time.delta_secs();
+                    }
 
-                    // Make enemy look at player
-                    if direction.length_squared() > 0.0 {
-                        let look_target = player_transform.translation;
-                        let look_dir = Vec3::new(
-                            look_target.x - enemy_transform.translation.x,
-                            0.0,
-                            look_target.z - enemy_transform.translation.z,
-                        )
-                        .normalize_or_zero();
+                    // Face the player while in sight, otherwise where we're heading
+                    let look_target = if awareness.target.is_some() { last_known } else { target };
+                    let look_dir = flat_direction(enemy_transform.translation, look_target);
+                    if look_dir.length_squared() > 0.0 {
+                        enemy_transform.look_to(look_dir, Vec3::Y);
+                    }
+                }
+            }
+            EnemyState::Search => {
+                if let Some(last_known) = awareness.last_known_position {
+                    let target = path.next_waypoint().unwrap_or(last_known);
 
-                        if look_dir.length_squared() > 0.0 {
-                            enemy_transform.look_to(look_dir, Vec3::Y);
+                    if enemy_transform.translation.xz().distance(last_known.xz()) < 1.0 {
+                        // Look around where the player was last noticed
+                        enemy_transform.rotate_y(behavior.search_turn_speed * time.delta_secs());
+                    } else {
+                        let direction = flat_direction(enemy_transform.translation, target);
+                        step = direction * movement.patrol_speed * time.delta_secs();
+                        if direction.length_squared() > 0.0 {
+                            enemy_transform.look_to(direction, Vec3::Y);
                         }
                     }
                 }
             }
+            EnemyState::Flee => {
+                // The flee point is away from the threat, without a path just run straight away from it
+                let target = match (path.next_waypoint(), awareness.last_known_position) {
+                    (Some(waypoint), _) => waypoint,
+                    (None, Some(threat)) => flee_point(enemy_transform.translation, threat, behavior.flee_distance),
+                    (None, None) => enemy_transform.translation,
+                };
+                let direction = flat_direction(enemy_transform.translation, target);
+                step = direction * movement.chase_speed * time.delta_secs();
+                if direction.length_squared() > 0.0 {
+                    enemy_transform.look_to(direction, Vec3::Y);
+                }
+            }
         }
 
-        // Keep enemy on ground
-        enemy_transform.translation.y = 1.0;
+        // Fall until the controller reports ground under the enemy, snapping keeps it there on slopes and steps
+        if controller_output.is_some_and(|output| output.grounded) {
+            velocity.fall_speed = 0.0;
+        } else {
+            velocity.fall_speed += ENEMY_GRAVITY * time.delta_secs();
+        }
+        step.y -= velocity.fall_speed * time.delta_secs();
+        controller.translation = Some(step);
+    }
+}
+
+/// Point `distance` away from a threat, on the far side of `position`
+pub fn flee_point(position: Vec3, threat: Vec3, distance: f32) -> Vec3 {
+    let away = flat_direction(threat, position);
+    let away = if away == Vec3::ZERO { Vec3::X } else { away };
+    position + away * distance
+}
+
+// Direction on the ground plane, height is left to the character controller
+fn flat_direction(from: Vec3, to: Vec3) -> Vec3 {
+    Vec3::new(to.x - from.x, 0.0, to.z - from.z).normalize_or_zero()
+}
+
+/// Enemies in the Attack state wind up, hit or shoot at their target if it is in range and cool down (server-only)
+pub fn enemy_attack_system(
+    mut commands: Commands,
+    mut enemies: Query<(
+        &Enemy,
+        &Transform,
+        &EnemyState,
+        &EnemyTarget,
+        &EnemyAwareness,
+        &EnemyPerception,
+        &EnemyMovement,
+        &EnemyAttack,
+        Option<&EnemyRanged>,
+        Option<&EnemyFlight>,
+        Option<&EnemyCover>,
+        &mut AttackTimer,
+    )>,
+    mut players: Query<
+        (Entity, &Player, &Transform, &mut Health, &mut PlayerStats),
+        (Without<Enemy>, Without<NeedsRespawn>),
+    >,
+    time: Res<Time>,
+) {
+    for (enemy, enemy_transform, state, target, awareness, perception, movement, attack, ranged, flight, cover, mut timer) in
+        enemies.iter_mut()
+    {
+        // Flyers start their dive from attack range but only hit up close
+        let reach = flight.map_or(movement.attack_range, |flight| flight.strike_range);
+        // Enemies in cover only attack while peeking out
+        let attacking = *state == EnemyState::Attack && cover.is_none_or(|cover| cover.exposed());
+        match timer.phase {
+            AttackPhase::Ready => {
+                if attacking {
+                    timer.phase = AttackPhase::WindUp;
+                    timer.elapsed = 0.0;
+                }
+            }
+            AttackPhase::WindUp => {
+                // Leaving the Attack state or ducking back into cover cancels the swing
+                if !attacking {
+                    timer.phase = AttackPhase::Ready;
+                    continue;
+                }
+
+                timer.elapsed += time.delta_secs();
+                // A dive strikes as soon as it reaches the target
+                let dive_arrived = flight.is_some()
+                    && target
+                        .player
+                        .and_then(|player| players.get(player).ok())
+                        .is_some_and(|(_, _, player_transform, ..)| {
+                            enemy_transform.translation.distance(player_transform.translation) <= reach
+                        });
+                if timer.elapsed < attack.windup && !dive_arrived {
+                    continue;
+                }
+
+                timer.phase = AttackPhase::Cooldown;
+                timer.elapsed = 0.0;
+
+                // The hit lands if the target is still in range at the end of the wind-up
+                let Some(Ok((player_entity, player, player_transform, mut health, mut stats))) =
+                    target.player.map(|target| players.get_mut(target))
+                else {
+                    continue;
+                };
+                if enemy_transform.translation.distance(player_transform.translation) > reach {
+                    continue;
+                }
+                // Another enemy may have landed the killing blow earlier this tick
+                if health.current <= 0.0 {
+                    continue;
+                }
+
+                // Ranged enemies fire at a target in sight, the projectile deals the damage when it lands
+                if let Some(ranged) = ranged {
+                    if awareness.target != Some(player_entity) {
+                        continue;
+                    }
+                    let origin = enemy_transform.translation + Vec3::Y * perception.eye_height;
+                    spawn_projectile(
+                        &mut commands,
+                        origin,
+                        ranged.projectile_radius,
+                        ProjectileFlight {
+                            velocity: aim_projectile(
+                                origin,
+                                player_transform.translation,
+                                ranged.projectile_speed,
+                                ranged.projectile_gravity,
+                            ),
+                            gravity: ranged.projectile_gravity,
+                            damage: attack.damage,
+                            remaining: ranged.projectile_lifetime,
+                            enemy_id: enemy.id,
+                        },
+                    );
+                    continue;
+                }
+
+                if damage_player(&mut commands, player_entity, &mut health, Some(&mut *stats), attack.damage) {
+                    println!("[SERVER] Enemy {} killed player {}", enemy.id, player.id);
+                } else {
+                    println!("[SERVER] Enemy {} hit player {} (Health: {:.0}/{:.0})",
+                        enemy.id, player.id, health.current, health.max);
+                }
+            }
+            AttackPhase::Cooldown => {
+                timer.elapsed += time.delta_secs();
+                if timer.elapsed >= attack.cooldown {
+                    timer.phase = AttackPhase::Ready;
+                }
+            }
+        }
+    }
+}
+
+/// Launch velocity that reaches `target` from `origin`, lobbed upwards to make up for gravity
+fn aim_projectile(origin: Vec3, target: Vec3, speed: f32, gravity: f32) -> Vec3 {
+    let offset = target - origin;
+    let flight_time = offset.length() / speed.max(0.01);
+    if flight_time <= 0.0 {
+        return Vec3::ZERO;
     }
+    offset / flight_time + Vec3::Y * 0.5 * gravity * flight_time
 }
 
 /// Server-side system to sync Transform back to replicated EnemyPosition
@@ -1,11 +1,40 @@
+pub mod bench;
+pub mod bot;
 pub mod enemy;
 pub mod cursor;
+pub mod navigation;
 pub mod player;
+pub mod projectile;
+pub mod ron_asset;
 pub mod shooting;
+pub mod spatial;
+pub mod spectator;
 pub mod ui;
+pub mod wave;
 pub mod world;
 
-pub use enemy::{spawn_enemies_system, enemy_fsm_system, enemy_flocking_system, enemy_movement_system, render_enemies_system, sync_enemy_position, sync_transform_to_enemy_position};
-pub use player::{render_replicated_players, spawn_players_system, sync_remote_player_rotation, sync_player_position, handle_rotation_input, handle_movement_input, sync_transform_to_position, handle_shoot_events, respawn_players_system};
-pub use ui::{setup_crosshair};
-pub use world::{init_server_state, setup_world, setup_server_world};
+pub use bot::{balance_bots, drive_bots, BotSettings};
+pub use enemy::archetype::{load_enemy_archetypes, reload_enemy_archetypes, EnemyArchetype, EnemyArchetypeLoader};
+pub use enemy::behavior_tree::BehaviorTreeDebug;
+pub use enemy::boss::{
+    animate_area_attack_warnings, boss_ability_system, boss_phase_system, render_area_attack_warnings,
+    resolve_area_attacks,
+};
+pub use enemy::cover::{build_cover_points, enemy_cover_system, CoverConfig, CoverPoints};
+pub use enemy::flying::flying_enemy_movement_system;
+pub use enemy::lod::{measure_enemy_ai, report_enemy_ai_lod, schedule_enemy_ai, AiLodConfig, AiLodMetrics, AiLodReport};
+pub use enemy::squad::{assign_squad_slots, SquadConfig};
+pub use enemy::systems::{
+    despawn_dead_enemies, dump_behavior_trees, enemy_attack_system, enemy_behavior_tree_system, enemy_flocking_system,
+    enemy_movement_system, enemy_perception_system, log_enemy_state_changes, refresh_enemy_appearance,
+    render_dead_enemies, render_enemies_system, render_enemy_states, sync_enemy_position,
+    sync_transform_to_enemy_position, track_enemy_state_changes,
+};
+pub use projectile::{render_projectiles_system, simulate_projectiles, sync_projectile_position};
+pub use player::{render_replicated_players, spawn_host_player, spawn_players_system, sync_remote_player_rotation, sync_player_position, handle_rotation_input, handle_movement_input, sync_transform_to_position, handle_shoot_events, respawn_players_system};
+pub use spatial::{rebuild_spatial_index, SpatialIndex};
+pub use spectator::{handle_spectate_requests, manage_spectator_camera, send_spectate_toggle, spawn_spectator_camera, update_spectator_camera};
+pub use ui::{setup_boss_bar, update_boss_bar, setup_crosshair, setup_match_timer, setup_playback_hud, update_match_timer, update_playback_hud, setup_wave_hud, update_wave_hud};
+pub use wave::{pacing_director_system, setup_wave_director, track_pacing_signals, wave_director_system, Pacing, PacingConfig, WaveConfig};
+pub use navigation::{build_navmesh, enemy_pathfinding_system, NavMesh, NavMeshConfig};
+pub use world::{init_server_state, load_level, setup_world, setup_server_world, spawn_level_geometry, Level, LevelLoader, LevelReady};
@@ -0,0 +1,64 @@
+use bevy::prelude::*;
+
+/// Tunables for navmesh generation and path following (server-only)
+#[derive(Resource)]
+pub struct NavMeshConfig {
+    /// Half size of the square area covered by the navmesh, matches the ground plane
+    pub half_extent: f32,
+    pub cell_size: f32,
+    /// Obstacles are grown by this much so agents don't scrape along walls
+    pub agent_radius: f32,
+    /// Height of the space that must be free above the ground for a cell to be walkable
+    pub agent_height: f32,
+    /// Colliders below this height are floor, not obstacles
+    pub step_height: f32,
+    /// Path again when the target moved further than this from the end of the path
+    pub repath_distance: f32,
+    /// Minimum seconds between two path queries of the same enemy
+    pub repath_interval: f32,
+    /// Distance at which a waypoint counts as reached
+    pub waypoint_tolerance: f32,
+}
+
+impl Default for NavMeshConfig {
+    fn default() -> Self {
+        Self {
+            half_extent: 50.0,
+            cell_size: 0.5,
+            agent_radius: 0.6,
+            agent_height: 1.8,
+            step_height: 0.3,
+            repath_distance: 1.0,
+            repath_interval: 0.25,
+            waypoint_tolerance: 0.3,
+        }
+    }
+}
+
+/// Path an enemy is following (server-only)
+#[derive(Component, Default)]
+pub struct NavPath {
+    pub waypoints: Vec<Vec3>,
+    pub current: usize,
+    /// Target the path was computed for
+    pub goal: Option<Vec3>,
+    /// Seconds since the last path query
+    pub since_repath: f32,
+}
+
+impl NavPath {
+    /// Waypoint to steer towards, None once the path is done
+    pub fn next_waypoint(&self) -> Option<Vec3> {
+        self.waypoints.get(self.current).copied()
+    }
+
+    pub fn is_finished(&self) -> bool {
+        self.current >= self.waypoints.len()
+    }
+
+    pub fn clear(&mut self) {
+        self.waypoints.clear();
+        self.current = 0;
+        self.goal = None;
+    }
+}
@@ -0,0 +1,7 @@
+pub mod components;
+pub mod navmesh;
+pub mod systems;
+
+pub use components::{NavMeshConfig, NavPath};
+pub use navmesh::NavMesh;
+pub use systems::*;
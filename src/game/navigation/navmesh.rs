@@ -0,0 +1,336 @@
+use bevy::prelude::*;
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashMap, HashSet};
+
+/// Walkability grid over the arena floor, built from the level's static colliders (server-only)
+#[derive(Resource)]
+pub struct NavMesh {
+    /// World position (x, z) of the corner of cell (0, 0)
+    pub origin: Vec2,
+    pub cell_size: f32,
+    pub width: usize,
+    pub depth: usize,
+    /// Height paths are placed at, matches the enemies' ground height
+    pub height: f32,
+    walkable: Vec<bool>,
+}
+
+pub type Cell = (usize, usize);
+
+// Neighbor offsets with their step cost, diagonals cost sqrt(2)
+const NEIGHBORS: [(isize, isize, f32); 8] = [
+    (1, 0, 1.0),
+    (-1, 0, 1.0),
+    (0, 1, 1.0),
+    (0, -1, 1.0),
+    (1, 1, std::f32::consts::SQRT_2),
+    (1, -1, std::f32::consts::SQRT_2),
+    (-1, 1, std::f32::consts::SQRT_2),
+    (-1, -1, std::f32::consts::SQRT_2),
+];
+
+impl NavMesh {
+    pub fn new(origin: Vec2, cell_size: f32, width: usize, depth: usize, height: f32, walkable: Vec<bool>) -> Self {
+        Self {
+            origin,
+            cell_size,
+            width,
+            depth,
+            height,
+            walkable,
+        }
+    }
+
+    pub fn cell_of(&self, position: Vec3) -> Option<Cell> {
+        let x = ((position.x - self.origin.x) / self.cell_size).floor();
+        let z = ((position.z - self.origin.y) / self.cell_size).floor();
+        if x < 0.0 || z < 0.0 || x >= self.width as f32 || z >= self.depth as f32 {
+            return None;
+        }
+        Some((x as usize, z as usize))
+    }
+
+    pub fn center_of(&self, (x, z): Cell) -> Vec3 {
+        Vec3::new(
+            self.origin.x + (x as f32 + 0.5) * self.cell_size,
+            self.height,
+            self.origin.y + (z as f32 + 0.5) * self.cell_size,
+        )
+    }
+
+    pub fn is_walkable(&self, (x, z): Cell) -> bool {
+        self.walkable[z * self.width + x]
+    }
+
+    pub fn walkable_count(&self) -> usize {
+        self.walkable.iter().filter(|walkable| **walkable).count()
+    }
+
+    fn neighbor(&self, (x, z): Cell, dx: isize, dz: isize) -> Option<Cell> {
+        let x = x.checked_add_signed(dx)?;
+        let z = z.checked_add_signed(dz)?;
+        (x < self.width && z < self.depth).then_some((x, z))
+    }
+
+    /// Closest walkable cell to a position, for targets standing against or inside an obstacle
+    pub fn nearest_walkable(&self, position: Vec3) -> Option<Cell> {
+        let clamped = Vec3::new(
+            position.x.clamp(self.origin.x, self.origin.x + self.width as f32 * self.cell_size - 0.01),
+            position.y,
+            position.z.clamp(self.origin.y, self.origin.y + self.depth as f32 * self.cell_size - 0.01),
+        );
+        let (cx, cz) = self.cell_of(clamped)?;
+
+        // Search growing square rings around the cell
+        let max_ring = self.width.max(self.depth) as isize;
+        for ring in 0..max_ring {
+            let mut best: Option<(Cell, f32)> = None;
+            for dz in -ring..=ring {
+                for dx in -ring..=ring {
+                    if dx.abs() != ring && dz.abs() != ring {
+                        continue;
+                    }
+                    let Some(cell) = self.neighbor((cx, cz), dx, dz) else {
+                        continue;
+                    };
+                    if !self.is_walkable(cell) {
+                        continue;
+                    }
+                    let distance = self.center_of(cell).xz().distance_squared(position.xz());
+                    if best.is_none_or(|(_, best_distance)| distance < best_distance) {
+                        best = Some((cell, distance));
+                    }
+                }
+            }
+            if let Some((cell, _)) = best {
+                return Some(cell);
+            }
+        }
+        None
+    }
+
+    /// A* over the grid followed by line-of-sight smoothing.
+    /// Returns world waypoints from just after `from` up to `to`.
+    pub fn find_path(&self, from: Vec3, to: Vec3) -> Option<Vec<Vec3>> {
+        let start = self.nearest_walkable(from)?;
+        let goal = self.nearest_walkable(to)?;
+
+        let cells = self.a_star(start, goal)?;
+        let mut path = self.smooth(&cells);
+
+        // End exactly at the target when it's reachable, otherwise at the closest walkable cell
+        if self.cell_of(to).is_some_and(|cell| cell == goal) {
+            if let Some(last) = path.last_mut() {
+                *last = Vec3::new(to.x, self.height, to.z);
+            }
+        }
+        Some(path)
+    }
+
+    fn a_star(&self, start: Cell, goal: Cell) -> Option<Vec<Cell>> {
+        let mut open = BinaryHeap::new();
+        let mut came_from: HashMap<Cell, Cell> = HashMap::new();
+        let mut cost: HashMap<Cell, f32> = HashMap::new();
+        let mut closed: HashSet<Cell> = HashSet::new();
+
+        cost.insert(start, 0.0);
+        open.push(OpenCell {
+            cell: start,
+            estimate: self.heuristic(start, goal),
+        });
+
+        while let Some(OpenCell { cell, .. }) = open.pop() {
+            // Cells pushed again with a cheaper cost leave stale entries behind, expand each cell once
+            if !closed.insert(cell) {
+                continue;
+            }
+            if cell == goal {
+                let mut cells = vec![cell];
+                let mut current = cell;
+                while let Some(previous) = came_from.get(&current) {
+                    current = *previous;
+                    cells.push(current);
+                }
+                cells.reverse();
+                return Some(cells);
+            }
+
+            let current_cost = cost[&cell];
+            for (dx, dz, step) in NEIGHBORS {
+                let Some(next) = self.neighbor(cell, dx, dz) else {
+                    continue;
+                };
+                if closed.contains(&next) || !self.is_walkable(next) {
+                    continue;
+                }
+                // No cutting corners of obstacles on diagonal steps
+                if dx != 0 && dz != 0 {
+                    let side_a = self.neighbor(cell, dx, 0).is_some_and(|c| self.is_walkable(c));
+                    let side_b = self.neighbor(cell, 0, dz).is_some_and(|c| self.is_walkable(c));
+                    if !side_a || !side_b {
+                        continue;
+                    }
+                }
+
+                let next_cost = current_cost + step;
+                if cost.get(&next).is_none_or(|known| next_cost < *known) {
+                    cost.insert(next, next_cost);
+                    came_from.insert(next, cell);
+                    open.push(OpenCell {
+                        cell: next,
+                        estimate: next_cost + self.heuristic(next, goal),
+                    });
+                }
+            }
+        }
+        None
+    }
+
+    // Octile distance, exact on an 8-connected grid without obstacles
+    fn heuristic(&self, (ax, az): Cell, (bx, bz): Cell) -> f32 {
+        let dx = ax.abs_diff(bx) as f32;
+        let dz = az.abs_diff(bz) as f32;
+        dx.max(dz) + (std::f32::consts::SQRT_2 - 1.0) * dx.min(dz)
+    }
+
+    /// Drop every cell that can be skipped by walking in a straight line (string pulling)
+    fn smooth(&self, cells: &[Cell]) -> Vec<Vec3> {
+        let mut path = Vec::new();
+        let mut anchor = 0;
+
+        while anchor + 1 < cells.len() {
+            // Furthest cell still directly walkable from the anchor
+            let mut furthest = anchor + 1;
+            for candidate in (anchor + 2..cells.len()).rev() {
+                if self.line_walkable(self.center_of(cells[anchor]), self.center_of(cells[candidate])) {
+                    furthest = candidate;
+                    break;
+                }
+            }
+            path.push(self.center_of(cells[furthest]));
+            anchor = furthest;
+        }
+
+        if path.is_empty() {
+            path.push(self.center_of(cells[0]));
+        }
+        path
+    }
+
+    /// Whether a straight line only crosses walkable cells, sampled at a quarter cell
+    pub fn line_walkable(&self, from: Vec3, to: Vec3) -> bool {
+        let distance = from.xz().distance(to.xz());
+        let steps = (distance / (self.cell_size * 0.25)).ceil().max(1.0) as usize;
+        (0..=steps).all(|step| {
+            let point = from.lerp(to, step as f32 / steps as f32);
+            self.cell_of(point).is_some_and(|cell| self.is_walkable(cell))
+        })
+    }
+}
+
+// Min-heap entry for A*, ordered by lowest estimated total cost
+struct OpenCell {
+    cell: Cell,
+    estimate: f32,
+}
+
+impl PartialEq for OpenCell {
+    fn eq(&self, other: &Self) -> bool {
+        self.estimate == other.estimate
+    }
+}
+
+impl Eq for OpenCell {}
+
+impl PartialOrd for OpenCell {
+    fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for OpenCell {
+    fn cmp(&self, other: &Self) -> Ordering {
+        other.estimate.total_cmp(&self.estimate)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    // Grid from rows of text, one row per z and one character per x: '#' blocked, anything else walkable
+    fn grid(rows: &[&str]) -> NavMesh {
+        let width = rows[0].len();
+        let walkable = rows.iter().flat_map(|row| row.chars().map(|cell| cell != '#')).collect();
+        NavMesh::new(Vec2::ZERO, 1.0, width, rows.len(), 1.0, walkable)
+    }
+
+    fn center(navmesh: &NavMesh, cell: Cell) -> Vec3 {
+        navmesh.center_of(cell)
+    }
+
+    #[test]
+    fn path_goes_around_obstacle() {
+        let navmesh = grid(&[
+            ".....#....",
+            ".....#....",
+            ".....#....",
+            ".....#....",
+            ".....#....",
+            ".....#....",
+            "..........",
+            "..........",
+        ]);
+        let from = center(&navmesh, (1, 1));
+        let to = center(&navmesh, (8, 1));
+
+        let path = navmesh.find_path(from, to).expect("goal is reachable through the gap");
+        assert_eq!(path.last().copied(), Some(to));
+        // The path passes below the wall and every leg stays on walkable cells
+        assert!(path.iter().any(|waypoint| waypoint.z > 6.0));
+        let mut previous = from;
+        for waypoint in path {
+            assert!(navmesh.line_walkable(previous, waypoint));
+            previous = waypoint;
+        }
+    }
+
+    #[test]
+    fn enclosed_goal_has_no_path() {
+        let navmesh = grid(&[
+            "........",
+            "..####..",
+            "..#..#..",
+            "..####..",
+            "........",
+        ]);
+        let from = center(&navmesh, (0, 0));
+        let to = center(&navmesh, (3, 2));
+
+        assert!(navmesh.find_path(from, to).is_none());
+    }
+
+    #[test]
+    fn diagonal_steps_do_not_cut_corners() {
+        // Both sides of the diagonal blocked: the only connection is through the corner
+        let navmesh = grid(&[
+            ".#",
+            "#.",
+        ]);
+        assert!(navmesh.a_star((0, 0), (1, 1)).is_none());
+
+        // One side blocked: the path walks around the corner instead of across it
+        let navmesh = grid(&[
+            ".#",
+            "..",
+        ]);
+        assert_eq!(navmesh.a_star((0, 0), (1, 1)), Some(vec![(0, 0), (0, 1), (1, 1)]));
+
+        // Open corner: a single diagonal step
+        let navmesh = grid(&[
+            "..",
+            "..",
+        ]);
+        assert_eq!(navmesh.a_star((0, 0), (1, 1)), Some(vec![(0, 0), (1, 1)]));
+    }
+}
@@ -0,0 +1,144 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+
+use super::components::{NavMeshConfig, NavPath};
+use super::navmesh::NavMesh;
+use crate::game::enemy::components::{get_current_waypoint, EnemyAwareness, EnemyBehavior, EnemyFlight, EnemyState, PatrolData};
+use crate::game::enemy::cover::EnemyCover;
+use crate::game::enemy::flee_point;
+use crate::game::enemy::lod::AiLod;
+use crate::game::enemy::squad::SquadSlot;
+use crate::game::world::level::LevelGeometry;
+use crate::network::protocol::Enemy;
+
+/// Height of an enemy's origin above the ground, paths are placed on it
+const ENEMY_HEIGHT: f32 = 1.0;
+
+/// Build the navmesh by probing the level's static colliders cell by cell (server-side)
+pub fn build_navmesh(
+    mut commands: Commands,
+    config: Res<NavMeshConfig>,
+    rapier_context: ReadRapierContext,
+    level_geometry: Query<Option<&RapierColliderHandle>, With<LevelGeometry>>,
+    mut colliders_registered: Local<bool>,
+) {
+    // Level colliders need to be in the physics world, and stepped once, before they can be queried
+    if level_geometry.iter().any(|handle| handle.is_none()) {
+        return;
+    }
+    if !*colliders_registered {
+        *colliders_registered = true;
+        return;
+    }
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+
+    let cells = (config.half_extent * 2.0 / config.cell_size).ceil() as usize;
+    let origin = Vec2::splat(-config.half_extent);
+
+    // Probe the space an agent occupies above the floor, grown by the agent radius
+    let probe_half_height = (config.agent_height - config.step_height) * 0.5;
+    let probe_half_width = config.cell_size * 0.5 + config.agent_radius;
+    let probe = Collider::cuboid(probe_half_width, probe_half_height, probe_half_width);
+    let probe_y = config.step_height + probe_half_height;
+
+    let mut walkable = vec![true; cells * cells];
+    rapier_context.with_query_pipeline(QueryFilter::only_fixed(), |query_pipeline| {
+        for z in 0..cells {
+            for x in 0..cells {
+                let center = Vec3::new(
+                    origin.x + (x as f32 + 0.5) * config.cell_size,
+                    probe_y,
+                    origin.y + (z as f32 + 0.5) * config.cell_size,
+                );
+                walkable[z * cells + x] = query_pipeline
+                    .intersect_shape(center, Quat::IDENTITY, &probe)
+                    .next()
+                    .is_none();
+            }
+        }
+    });
+
+    let navmesh = NavMesh::new(origin, config.cell_size, cells, cells, ENEMY_HEIGHT, walkable);
+    println!(
+        "[SERVER] Navmesh built: {}x{} cells, {} walkable",
+        cells,
+        cells,
+        navmesh.walkable_count()
+    );
+    commands.insert_resource(navmesh);
+}
+
+/// Keep each enemy's path to its patrol waypoint, squad slot or the target's last known position up to date (server-side)
+pub fn enemy_pathfinding_system(
+    navmesh: Option<Res<NavMesh>>,
+    config: Res<NavMeshConfig>,
+    mut enemies: Query<
+        (
+            &Transform,
+            &EnemyState,
+            &PatrolData,
+            &EnemyAwareness,
+            &SquadSlot,
+            &EnemyCover,
+            &EnemyBehavior,
+            &AiLod,
+            &mut NavPath,
+        ),
+        // Flyers don't walk the navmesh
+        (With<Enemy>, Without<EnemyFlight>),
+    >,
+    time: Res<Time>,
+) {
+    // Until the navmesh is built enemies walk straight at their targets
+    let Some(navmesh) = navmesh else {
+        return;
+    };
+
+    for (transform, state, patrol, awareness, slot, cover, behavior, lod, mut path) in enemies.iter_mut() {
+        path.since_repath += time.delta_secs();
+
+        let position = transform.translation;
+        let goal = match *state {
+            EnemyState::Patrol => Some(get_current_waypoint(patrol)),
+            EnemyState::Chase => cover.goal().or(slot.goal()).or(awareness.last_known_position),
+            // Attacking from cover means moving between the cover point and the spot to peek from
+            EnemyState::Attack => cover.goal(),
+            EnemyState::Search => awareness.last_known_position,
+            // Paths are cleared on state changes, so the flee point is picked once per flight
+            EnemyState::Flee => path.goal.or_else(|| {
+                awareness
+                    .last_known_position
+                    .map(|threat| flee_point(position, threat, behavior.flee_distance))
+            }),
+            // Standing still, corpses don't move on their own
+            EnemyState::Idle | EnemyState::Stunned | EnemyState::Dead => None,
+        };
+
+        let Some(goal) = goal else {
+            path.clear();
+            continue;
+        };
+
+        let target_moved = path
+            .goal
+            .is_none_or(|old_goal| old_goal.xz().distance(goal.xz()) > config.repath_distance);
+        let can_repath = path.goal.is_none() || path.since_repath >= config.repath_interval;
+
+        // Only enemies thinking this tick repath, the others keep walking their current path
+        if target_moved && can_repath && lod.active {
+            path.since_repath = 0.0;
+            path.goal = Some(goal);
+            path.current = 0;
+            path.waypoints = navmesh.find_path(position, goal).unwrap_or_default();
+        }
+
+        while let Some(waypoint) = path.next_waypoint() {
+            if waypoint.xz().distance(position.xz()) > config.waypoint_tolerance {
+                break;
+            }
+            path.current += 1;
+        }
+    }
+}
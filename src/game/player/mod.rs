@@ -7,4 +7,4 @@ pub mod shooting;
 pub mod systems;
 
 pub use rendering::{render_replicated_players, sync_remote_player_rotation, sync_player_position};
-pub use systems::{spawn_players_system, handle_rotation_input, handle_movement_input, sync_transform_to_position, handle_shoot_events, respawn_players_system};
+pub use systems::{damage_player, spawn_host_player, spawn_player, spawn_players_system, handle_rotation_input, handle_movement_input, sync_transform_to_position, handle_shoot_events, respawn_players_system};
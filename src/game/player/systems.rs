@@ -4,9 +4,16 @@ use bevy_replicon::prelude::*;
 use bevy_replicon::shared::backend::connected_client::NetworkId;
 use bevy_replicon_renet::renet::ServerEvent;
 
+use renet_netcode::NetcodeServerTransport;
+
+use crate::game::bot::BotPlayer;
+use crate::game::spectator::spawn_spectator;
+use crate::network::client::JoinAsSpectator;
+use crate::network::server::sender_client_id;
 use crate::game::world::state::PlayerCount;
 use crate::game::player::components::PlayerPhysicsBundle;
-use crate::network::protocol::{Player, PlayerPosition, PlayerRotation, Health, RotationInput, MovementInput, ShootEvent, Enemy};
+use crate::network::protocol::{Player, PlayerPosition, PlayerRotation, Health, RotationInput, MovementInput, ShootEvent, Enemy, Spectator, PlayerStats, ConnectRequest, HOST_CLIENT_ID, MAX_PLAYERS};
+use crate::game::enemy::{components::{EnemyBehavior, EnemyHit, EnemyState, EnemyThreat, Gunshot, StunBuildup, ThreatTable}, kill_enemy, stun_enemy};
 
 // Marker component for players that need to respawn
 #[derive(Component)]
@@ -16,67 +23,116 @@ pub fn spawn_players_system(
     mut commands: Commands,
     mut server_events: MessageReader<ServerEvent>,
     mut player_count: ResMut<PlayerCount>,
+    transport: Option<Res<NetcodeServerTransport>>,
+    players: Query<(Entity, &Player)>,
+    human_players: Query<&Player, Without<BotPlayer>>, // Bots give up their slots to humans
+    spectators: Query<(Entity, &Spectator)>,
 ) {
+    // Track slots taken this frame since despawns and spawns are deferred
+    let mut active_players = human_players.iter().count();
+
     for event in server_events.read() {
-        if let ServerEvent::ClientConnected { client_id } = event {
-            // Position players in a circle around the origin
-            let angle = player_count.0 as f32 * std::f32::consts::TAU / 4.0; // Distribute evenly
-            let radius = 3.0;
-            let x = angle.cos() * radius;
-            let z = angle.sin() * radius;
-
-            let color_index = player_count.0 as u8;
-            player_count.0 += 1;
-
-            // Spawn player entity with physics (server-side)
-            // Spawn at y=5.0 so we can see them fall and collide with ground
-            let spawn_y = 5.0;
-            let player_entity = commands
-                .spawn((
-                    Player {
-                        id: *client_id,
-                        color_index,
-                    },
-                    PlayerPosition { x, y: spawn_y, z },
-                    PlayerRotation { yaw: 0.0, pitch: 0.0 },
-                    Health { current: 100.0, max: 100.0 },
-                    Transform::from_xyz(x, spawn_y, z),
-                    GlobalTransform::default(),
-                    PlayerPhysicsBundle::default(),
-                    Replicated,
-                ))
-                .id();
-
-            println!(
-                "Spawned player entity {:?} for client {} at position ({}, {}, {})",
-                player_entity, client_id, x, spawn_y, z
-            );
+        match event {
+            ServerEvent::ClientConnected { client_id } => {
+                let connect_request = transport
+                    .as_ref()
+                    .and_then(|transport| transport.user_data(*client_id))
+                    .map(|user_data| ConnectRequest::from_user_data(&user_data))
+                    .unwrap_or_default();
+
+                if connect_request.spectator {
+                    spawn_spectator(&mut commands, *client_id);
+                } else if active_players >= MAX_PLAYERS {
+                    println!("[SERVER] No free player slot for client {}, joining as spectator", client_id);
+                    spawn_spectator(&mut commands, *client_id);
+                } else {
+                    active_players += 1;
+                    spawn_player(&mut commands, &mut player_count, *client_id);
+                }
+            }
+            ServerEvent::ClientDisconnected { client_id, .. } => {
+                // Free the slot so spectators can take it
+                for (entity, player) in players.iter() {
+                    if player.id == *client_id {
+                        if human_players.contains(entity) {
+                            active_players -= 1;
+                        }
+                        commands.entity(entity).despawn();
+                    }
+                }
+                for (entity, spectator) in spectators.iter() {
+                    if spectator.id == *client_id {
+                        commands.entity(entity).despawn();
+                    }
+                }
+            }
         }
     }
 }
 
+// Spawn the listen-server host's own player or spectator (host mode only)
+pub fn spawn_host_player(
+    mut commands: Commands,
+    mut player_count: ResMut<PlayerCount>,
+    join_as_spectator: Res<JoinAsSpectator>,
+) {
+    if join_as_spectator.0 {
+        spawn_spectator(&mut commands, HOST_CLIENT_ID);
+    } else {
+        spawn_player(&mut commands, &mut player_count, HOST_CLIENT_ID);
+    }
+}
+
+// Spawn a player entity with physics for the given client (server-side)
+pub fn spawn_player(commands: &mut Commands, player_count: &mut PlayerCount, client_id: u64) -> Entity {
+    // Position players in a circle around the origin
+    let angle = player_count.0 as f32 * std::f32::consts::TAU / 4.0; // Distribute evenly
+    let radius = 3.0;
+    let x = angle.cos() * radius;
+    let z = angle.sin() * radius;
+
+    let color_index = player_count.0 as u8;
+    player_count.0 += 1;
+
+    // Spawn at y=5.0 so we can see them fall and collide with ground
+    let spawn_y = 5.0;
+    let player_entity = commands
+        .spawn((
+            Player {
+                id: client_id,
+                color_index,
+            },
+            PlayerPosition { x, y: spawn_y, z },
+            PlayerRotation { yaw: 0.0, pitch: 0.0 },
+            Health { current: 100.0, max: 100.0 },
+            PlayerStats::default(),
+            Transform::from_xyz(x, spawn_y, z),
+            GlobalTransform::default(),
+            PlayerPhysicsBundle::default(),
+            Replicated,
+        ))
+        .id();
+
+    println!(
+        "Spawned player entity {:?} for client {} at position ({}, {}, {})",
+        player_entity, client_id, x, spawn_y, z
+    );
+
+    player_entity
+}
+
 pub fn handle_rotation_input(
     mut rotation_inputs: MessageReader<FromClient<RotationInput>>,
     mut players: Query<(&Player, &mut PlayerRotation)>,
     client_entities: Query<&NetworkId>,
 ) {
     for input in rotation_inputs.read() {
-        let sender_entity = match input.client_id {
-            ClientId::Client(entity) => entity,
-            ClientId::Server => {
-                // Skip server messages (shouldn't happen for client input)
-                continue;
-            }
-        };
-
-        // Get the NetworkId (u64) from the client entity
-        let Ok(network_id) = client_entities.get(sender_entity) else {
-            warn!("Received rotation input from unknown client entity {:?}", sender_entity);
+        // Get the NetworkId (u64) from the client entity, or the host id for local input
+        let Some(client_id) = sender_client_id(input.client_id, &client_entities) else {
+            warn!("Received rotation input from unknown client {:?}", input.client_id);
             continue;
         };
 
-        let client_id = network_id.get();
-
         // Find the player with matching id and update their rotation
         for (player, mut rotation) in players.iter_mut() {
             if player.id == client_id {
@@ -96,20 +152,11 @@ pub fn handle_movement_input(
     const MOVE_SPEED: f32 = 5.0; // Units per second
 
     for input in movement_inputs.read() {
-        let sender_entity = match input.client_id {
-            ClientId::Client(entity) => entity,
-            ClientId::Server => {
-                continue;
-            }
-        };
-
-        let Ok(network_id) = client_entities.get(sender_entity) else {
-            warn!("Received movement input from unknown client entity {:?}", sender_entity);
+        let Some(client_id) = sender_client_id(input.client_id, &client_entities) else {
+            warn!("Received movement input from unknown client {:?}", input.client_id);
             continue;
         };
 
-        let client_id = network_id.get();
-
         // Find the player and update their velocity based on movement input
         for (player, rotation, mut velocity) in players.iter_mut() {
             if player.id == client_id {
@@ -161,25 +208,23 @@ pub fn handle_shoot_events(
     client_entities: Query<&NetworkId>,
     players: Query<(Entity, &Player)>,
     mut player_healths: Query<&mut Health, With<Player>>,
-    enemies: Query<Entity, With<Enemy>>,
+    mut player_stats: Query<&mut PlayerStats>,
+    mut enemies: Query<
+        (&Enemy, &mut Health, &mut EnemyState, &EnemyBehavior, &mut StunBuildup, &EnemyThreat, &mut ThreatTable),
+        Without<Player>,
+    >,
+    mut gunshots: MessageWriter<Gunshot>,
+    mut enemy_hits: MessageWriter<EnemyHit>,
     rapier_context: ReadRapierContext,
+    time: Res<Time>,
 ) {
     const DAMAGE_PER_HIT: f32 = 25.0;
     
     for event in shoot_events.read() {
-        let sender_entity = match event.client_id {
-            ClientId::Client(entity) => entity,
-            ClientId::Server => {
-                continue;
-            }
-        };
-
-        let Ok(network_id) = client_entities.get(sender_entity) else {
-            warn!("Received shoot event from unknown client entity {:?}", sender_entity);
+        let Some(client_id) = sender_client_id(event.client_id, &client_entities) else {
+            warn!("Received shoot event from unknown client {:?}", event.client_id);
             continue;
         };
-
-        let client_id = network_id.get();
         
         // Find the shooter's player entity
         let shooter_entity = players
@@ -192,6 +237,11 @@ pub fn handle_shoot_events(
             continue;
         };
 
+        // Nearby enemies hear the shot whether or not it hits anything
+        gunshots.write(Gunshot {
+            origin: event.message.origin,
+        });
+
         // Perform raycast on server
         let Ok(rapier_context) = rapier_context.single() else {
             warn!("No rapier context available");
@@ -209,22 +259,52 @@ pub fn handle_shoot_events(
                 true,
             ) {
                 // Check if we hit an enemy
-                if enemies.contains(hit_entity) {
-                    println!("[SERVER] Client {} hit enemy {:?} at {:.2}m", 
-                        client_id, hit_entity, toi);
+                if let Ok((enemy, mut health, mut state, behavior, mut stun, threat, mut threat_table)) = enemies.get_mut(hit_entity) {
+                    // Corpses still have colliders, don't kill them twice
+                    if health.current <= 0.0 {
+                        return;
+                    }
+
+                    health.current -= DAMAGE_PER_HIT;
+                    // Whoever hurts an enemy the most draws its attention
+                    threat_table.add(shooter_entity, DAMAGE_PER_HIT * threat.damage_threat);
+
+                    enemy_hits.write(EnemyHit {
+                        killed: health.current <= 0.0,
+                    });
+                    if health.current <= 0.0 {
+                        health.current = 0.0;
+                        println!("[SERVER] Client {} killed enemy {} at {:.2}m",
+                            client_id, enemy.id, toi);
+                        kill_enemy(&mut commands, hit_entity, &mut state, event.message.direction);
+                        if let Ok(mut stats) = player_stats.get_mut(shooter_entity) {
+                            stats.kills += 1;
+                        }
+                    } else {
+                        println!("[SERVER] Client {} hit enemy {} at {:.2}m (Health: {:.0}/{:.0})",
+                            client_id, enemy.id, toi, health.current, health.max);
+                        // Hits piling up quickly relative to the enemy's health knock it off balance
+                        if stun.add_hit(behavior, DAMAGE_PER_HIT, health.max, time.elapsed_secs()) {
+                            stun_enemy(&mut state);
+                        }
+                    }
                 }
                 // Check if we hit a player
                 else if let Ok((hit_entity, hit_player)) = players.get(hit_entity) {
                     // Apply damage to the hit player
                     if let Ok(mut health) = player_healths.get_mut(hit_entity) {
-                        health.current -= DAMAGE_PER_HIT;
-                        
+                        // Already dead and waiting to respawn
                         if health.current <= 0.0 {
-                            health.current = 0.0;
+                            return;
+                        }
+
+                        let mut victim_stats = player_stats.get_mut(hit_entity).ok();
+                        if damage_player(&mut commands, hit_entity, &mut health, victim_stats.as_deref_mut(), DAMAGE_PER_HIT) {
                             println!("[SERVER] Client {} killed player {} at {:.2}m", 
                                 client_id, hit_player.id, toi);
-                            // Mark player for respawn
-                            commands.entity(hit_entity).insert(NeedsRespawn);
+                            if let Ok(mut stats) = player_stats.get_mut(shooter_entity) {
+                                stats.kills += 1;
+                            }
                         } else {
                             println!("[SERVER] Client {} hit player {} at {:.2}m (Health: {:.0}/{:.0})", 
                                 client_id, hit_player.id, toi, health.current, health.max);
@@ -236,6 +316,29 @@ pub fn handle_shoot_events(
     }
 }
 
+// Apply damage to a player, on death count it and mark them for respawn.
+// Shared by PvP hits and enemy attacks. Returns true if the player was killed.
+pub fn damage_player(
+    commands: &mut Commands,
+    entity: Entity,
+    health: &mut Health,
+    stats: Option<&mut PlayerStats>,
+    amount: f32,
+) -> bool {
+    health.current -= amount;
+    if health.current > 0.0 {
+        return false;
+    }
+
+    health.current = 0.0;
+    if let Some(stats) = stats {
+        stats.deaths += 1;
+    }
+    // Mark player for respawn
+    commands.entity(entity).insert(NeedsRespawn);
+    true
+}
+
 // Server-side system to respawn dead players
 pub fn respawn_players_system(
     mut commands: Commands,
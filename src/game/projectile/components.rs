@@ -0,0 +1,18 @@
+use bevy::prelude::*;
+
+/// How a projectile flies and what it does on impact (server-only)
+#[derive(Component)]
+pub struct ProjectileFlight {
+    pub velocity: Vec3,
+    /// Downward acceleration, 0 flies straight
+    pub gravity: f32,
+    pub damage: f32,
+    /// Seconds left before the projectile is removed without hitting anything
+    pub remaining: f32,
+    /// Id of the enemy that fired it, for the server log
+    pub enemy_id: u32,
+}
+
+/// Client-side marker for rendered projectiles
+#[derive(Component)]
+pub struct RenderedProjectile;
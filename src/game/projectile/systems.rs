@@ -0,0 +1,114 @@
+use bevy::prelude::*;
+use bevy_rapier3d::prelude::*;
+use bevy_replicon::prelude::*;
+
+use super::components::{ProjectileFlight, RenderedProjectile};
+use crate::game::player::{damage_player, systems::NeedsRespawn};
+use crate::network::protocol::{Enemy, Health, Player, PlayerStats, Projectile};
+
+/// Spawn a replicated projectile at `origin` (server-side)
+pub fn spawn_projectile(commands: &mut Commands, origin: Vec3, radius: f32, flight: ProjectileFlight) -> Entity {
+    commands
+        .spawn((
+            Projectile {
+                x: origin.x,
+                y: origin.y,
+                z: origin.z,
+                radius,
+            },
+            flight,
+            Transform::from_translation(origin),
+            Replicated,
+        ))
+        .id()
+}
+
+/// Move projectiles and sweep each step with a ray so fast ones can't tunnel through players or walls.
+/// Projectiles pass through enemies, so they don't hit whoever fired them (server-only)
+pub fn simulate_projectiles(
+    mut commands: Commands,
+    mut projectiles: Query<(Entity, &mut Transform, &mut Projectile, &mut ProjectileFlight)>,
+    mut players: Query<(&Player, &mut Health, &mut PlayerStats), (Without<Enemy>, Without<NeedsRespawn>)>,
+    enemies: Query<(), With<Enemy>>,
+    rapier_context: ReadRapierContext,
+    time: Res<Time>,
+) {
+    let Ok(rapier_context) = rapier_context.single() else {
+        return;
+    };
+    let delta = time.delta_secs();
+    let not_enemy = |entity: Entity| !enemies.contains(entity);
+    let filter = QueryFilter::default().exclude_sensors().predicate(&not_enemy);
+
+    for (entity, mut transform, mut projectile, mut flight) in projectiles.iter_mut() {
+        flight.remaining -= delta;
+        if flight.remaining <= 0.0 {
+            commands.entity(entity).despawn();
+            continue;
+        }
+
+        flight.velocity.y -= flight.gravity * delta;
+        let step = flight.velocity * delta;
+        let distance = step.length();
+        if distance <= f32::EPSILON {
+            continue;
+        }
+
+        let start = transform.translation;
+        let hit = rapier_context.with_query_pipeline(filter, |query_pipeline| {
+            query_pipeline.cast_ray(start, step / distance, distance + projectile.radius, true)
+        });
+
+        if let Some((hit_entity, _)) = hit {
+            commands.entity(entity).despawn();
+            let Ok((player, mut health, mut stats)) = players.get_mut(hit_entity) else {
+                continue;
+            };
+            if health.current <= 0.0 {
+                continue;
+            }
+
+            if damage_player(&mut commands, hit_entity, &mut health, Some(&mut *stats), flight.damage) {
+                println!("[SERVER] Enemy {} shot player {} dead", flight.enemy_id, player.id);
+            } else {
+                println!("[SERVER] Enemy {} shot player {} (Health: {:.0}/{:.0})",
+                    flight.enemy_id, player.id, health.current, health.max);
+            }
+            continue;
+        }
+
+        transform.translation += step;
+        projectile.x = transform.translation.x;
+        projectile.y = transform.translation.y;
+        projectile.z = transform.translation.z;
+    }
+}
+
+/// Draw replicated projectiles as small glowing spheres (client-side)
+pub fn render_projectiles_system(
+    mut commands: Commands,
+    mut meshes: ResMut<Assets<Mesh>>,
+    mut materials: ResMut<Assets<StandardMaterial>>,
+    projectiles: Query<(Entity, &Projectile), Without<RenderedProjectile>>,
+) {
+    for (entity, projectile) in projectiles.iter() {
+        commands.entity(entity).insert((
+            RenderedProjectile,
+            Mesh3d(meshes.add(Sphere::new(projectile.radius))),
+            MeshMaterial3d(materials.add(StandardMaterial {
+                base_color: Color::srgb(1.0, 0.5, 0.1),
+                emissive: LinearRgba::rgb(4.0, 1.5, 0.2),
+                ..default()
+            })),
+            Transform::from_xyz(projectile.x, projectile.y, projectile.z),
+            Visibility::default(),
+        ));
+    }
+}
+
+/// Copy replicated projectile positions into Transform (client-side)
+pub fn sync_projectile_position(mut projectiles: Query<(&Projectile, &mut Transform), With<RenderedProjectile>>) {
+    for (projectile, mut transform) in projectiles.iter_mut() {
+        transform.translation = Vec3::new(projectile.x, projectile.y, projectile.z);
+    }
+}
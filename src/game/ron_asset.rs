@@ -0,0 +1,39 @@
+use bevy::asset::io::Reader;
+use serde::de::DeserializeOwned;
+
+/// Error shared by the loaders of `.ron` asset files
+#[derive(Debug)]
+pub enum RonAssetError {
+    Io(std::io::Error),
+    Ron(ron::error::SpannedError),
+}
+
+impl std::fmt::Display for RonAssetError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            RonAssetError::Io(err) => write!(f, "could not read asset: {}", err),
+            RonAssetError::Ron(err) => write!(f, "could not parse asset: {}", err),
+        }
+    }
+}
+
+impl std::error::Error for RonAssetError {}
+
+impl From<std::io::Error> for RonAssetError {
+    fn from(err: std::io::Error) -> Self {
+        RonAssetError::Io(err)
+    }
+}
+
+impl From<ron::error::SpannedError> for RonAssetError {
+    fn from(err: ron::error::SpannedError) -> Self {
+        RonAssetError::Ron(err)
+    }
+}
+
+/// Read a whole asset file and deserialize it from RON
+pub async fn read_ron<T: DeserializeOwned>(reader: &mut dyn Reader) -> Result<T, RonAssetError> {
+    let mut bytes = Vec::new();
+    reader.read_to_end(&mut bytes).await?;
+    Ok(ron::de::from_bytes(&bytes)?)
+}
@@ -0,0 +1,77 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+/// Something stored in the spatial index
+#[derive(Clone, Copy)]
+pub struct SpatialEntry {
+    pub entity: Entity,
+    pub position: Vec3,
+    /// Flocking velocity for enemies, zero for players
+    pub velocity: Vec3,
+}
+
+/// Uniform grid on the ground plane, answers radius and nearest queries
+/// by only looking at the cells a query touches
+pub struct SpatialGrid {
+    cell_size: f32,
+    cells: HashMap<IVec2, Vec<SpatialEntry>>,
+}
+
+impl SpatialGrid {
+    pub fn new(cell_size: f32) -> Self {
+        Self {
+            cell_size,
+            cells: HashMap::new(),
+        }
+    }
+
+    /// Empty every cell, keeping their allocations for the next rebuild
+    pub fn clear(&mut self) {
+        for cell in self.cells.values_mut() {
+            cell.clear();
+        }
+    }
+
+    pub fn insert(&mut self, entry: SpatialEntry) {
+        let cell = self.cell_of(entry.position);
+        self.cells.entry(cell).or_default().push(entry);
+    }
+
+    fn cell_of(&self, position: Vec3) -> IVec2 {
+        IVec2::new(
+            (position.x / self.cell_size).floor() as i32,
+            (position.z / self.cell_size).floor() as i32,
+        )
+    }
+
+    /// Entries within `radius` of `center`
+    pub fn within(&self, center: Vec3, radius: f32) -> impl Iterator<Item = &SpatialEntry> + '_ {
+        let min = self.cell_of(center - Vec3::splat(radius));
+        let max = self.cell_of(center + Vec3::splat(radius));
+        let radius_squared = radius * radius;
+
+        (min.y..=max.y)
+            .flat_map(move |z| (min.x..=max.x).map(move |x| IVec2::new(x, z)))
+            .filter_map(|cell| self.cells.get(&cell))
+            .flatten()
+            .filter(move |entry| entry.position.distance_squared(center) <= radius_squared)
+    }
+}
+
+/// Living enemies and players, rebuilt at the start of every AI tick (server-only)
+#[derive(Resource)]
+pub struct SpatialIndex {
+    pub enemies: SpatialGrid,
+    pub players: SpatialGrid,
+}
+
+impl Default for SpatialIndex {
+    fn default() -> Self {
+        // About half the flocking neighbor range, so neighbor queries touch a handful of cells
+        const CELL_SIZE: f32 = 5.0;
+        Self {
+            enemies: SpatialGrid::new(CELL_SIZE),
+            players: SpatialGrid::new(CELL_SIZE),
+        }
+    }
+}
@@ -0,0 +1,36 @@
+use bevy::prelude::*;
+
+use super::components::{SpatialEntry, SpatialIndex};
+use crate::game::enemy::components::{EnemyState, EnemyVelocity};
+use crate::network::protocol::{Enemy, Health, PlayerPosition};
+
+/// Rebuild the spatial index from living enemies and players (server-side)
+pub fn rebuild_spatial_index(
+    mut index: ResMut<SpatialIndex>,
+    enemies: Query<(Entity, &Transform, &EnemyState, &EnemyVelocity), With<Enemy>>,
+    players: Query<(Entity, &Transform, &Health), (With<PlayerPosition>, Without<Enemy>)>,
+) {
+    index.enemies.clear();
+    for (entity, transform, state, velocity) in enemies.iter() {
+        if *state == EnemyState::Dead {
+            continue;
+        }
+        index.enemies.insert(SpatialEntry {
+            entity,
+            position: transform.translation,
+            velocity: velocity.velocity,
+        });
+    }
+
+    index.players.clear();
+    for (entity, transform, health) in players.iter() {
+        if health.current <= 0.0 {
+            continue;
+        }
+        index.players.insert(SpatialEntry {
+            entity,
+            position: transform.translation,
+            velocity: Vec3::ZERO,
+        });
+    }
+}
@@ -0,0 +1,34 @@
+use bevy::prelude::*;
+
+/// What the spectator camera is currently looking through
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum SpectatorMode {
+    /// Fly freely around the map
+    Free,
+    /// First-person view of the player with this id
+    Follow(u64),
+}
+
+/// Client-side camera used while the local client is spectating
+#[derive(Component)]
+pub struct SpectatorCamera {
+    pub mode: SpectatorMode,
+    /// Horizontal rotation in degrees
+    pub yaw: f32,
+    /// Vertical rotation in degrees
+    pub pitch: f32,
+    pub sensitivity: f32,
+    pub fly_speed: f32,
+}
+
+impl Default for SpectatorCamera {
+    fn default() -> Self {
+        Self {
+            mode: SpectatorMode::Free,
+            yaw: 0.0,
+            pitch: -20.0,
+            sensitivity: 0.035,
+            fly_speed: 10.0,
+        }
+    }
+}
@@ -0,0 +1,4 @@
+pub mod components;
+pub mod systems;
+
+pub use systems::{handle_spectate_requests, manage_spectator_camera, send_spectate_toggle, spawn_spectator, spawn_spectator_camera, update_spectator_camera};
@@ -0,0 +1,210 @@
+use bevy::{input::mouse::MouseMotion, prelude::*};
+use bevy_replicon::prelude::*;
+use bevy_replicon::shared::backend::connected_client::NetworkId;
+
+use super::components::{SpectatorCamera, SpectatorMode};
+use crate::game::bot::BotPlayer;
+use crate::game::player::spawn_player;
+use crate::game::world::state::PlayerCount;
+use crate::network::client::LocalClientId;
+use crate::network::server::sender_client_id;
+use crate::network::protocol::{Player, PlayerRotation, SpectateRequest, Spectator, MAX_PLAYERS};
+
+/// Eye height used when looking through a followed player
+const EYE_HEIGHT: f32 = 1.6;
+const PITCH_LOCK: f32 = 88.0;
+
+/// Spawn a replicated spectator entity for the given client (server-side)
+pub fn spawn_spectator(commands: &mut Commands, client_id: u64) -> Entity {
+    let spectator_entity = commands
+        .spawn((Spectator { id: client_id }, Replicated))
+        .id();
+
+    println!("[SERVER] Client {} joined as spectator", client_id);
+
+    spectator_entity
+}
+
+/// Switch clients between spectating and playing on request (server-side)
+pub fn handle_spectate_requests(
+    mut commands: Commands,
+    mut requests: MessageReader<FromClient<SpectateRequest>>,
+    mut player_count: ResMut<PlayerCount>,
+    client_entities: Query<&NetworkId>,
+    players: Query<(Entity, &Player)>,
+    human_players: Query<&Player, Without<BotPlayer>>,
+    spectators: Query<(Entity, &Spectator)>,
+) {
+    // Track slots taken this frame since despawns and spawns are deferred.
+    // Bots don't count, they leave when humans need the slot.
+    let mut active_players = human_players.iter().count();
+
+    for request in requests.read() {
+        let Some(client_id) = sender_client_id(request.client_id, &client_entities) else {
+            warn!("Received spectate request from unknown client {:?}", request.client_id);
+            continue;
+        };
+
+        if request.message.spectate {
+            let Some((player_entity, _)) = players.iter().find(|(_, player)| player.id == client_id) else {
+                continue;
+            };
+
+            commands.entity(player_entity).despawn();
+            active_players -= 1;
+            spawn_spectator(&mut commands, client_id);
+        } else {
+            let Some((spectator_entity, _)) = spectators.iter().find(|(_, spectator)| spectator.id == client_id) else {
+                continue;
+            };
+
+            if active_players >= MAX_PLAYERS {
+                println!("[SERVER] Client {} wants to play but all {} slots are taken", client_id, MAX_PLAYERS);
+                continue;
+            }
+
+            commands.entity(spectator_entity).despawn();
+            active_players += 1;
+            spawn_player(&mut commands, &mut player_count, client_id);
+        }
+    }
+}
+
+/// Send a spectate toggle to the server when the join key is pressed (client-side)
+pub fn send_spectate_toggle(
+    keys: Res<ButtonInput<KeyCode>>,
+    local_client_id: Res<LocalClientId>,
+    spectators: Query<&Spectator>,
+    mut spectate_writer: MessageWriter<SpectateRequest>,
+) {
+    if !keys.just_pressed(KeyCode::KeyJ) {
+        return;
+    }
+
+    let is_spectating = spectators.iter().any(|spectator| spectator.id == local_client_id.0);
+    spectate_writer.write(SpectateRequest {
+        spectate: !is_spectating,
+    });
+}
+
+/// Spawn or remove the spectator camera depending on the local client's role (client-side)
+pub fn manage_spectator_camera(
+    mut commands: Commands,
+    local_client_id: Res<LocalClientId>,
+    spectators: Query<&Spectator>,
+    cameras: Query<Entity, With<SpectatorCamera>>,
+) {
+    let is_spectating = spectators.iter().any(|spectator| spectator.id == local_client_id.0);
+
+    if is_spectating && cameras.is_empty() {
+        println!("Spectating - F: free/follow camera, Tab: next player, J: join the match");
+        spawn_spectator_camera(&mut commands);
+    } else if !is_spectating {
+        for entity in cameras.iter() {
+            commands.entity(entity).despawn();
+        }
+    }
+}
+
+/// Spawn a free-flying spectator camera (client-side)
+pub fn spawn_spectator_camera(commands: &mut Commands) -> Entity {
+    let camera = SpectatorCamera::default();
+    commands
+        .spawn((
+            Camera3d::default(),
+            Projection::Perspective(PerspectiveProjection {
+                fov: 103.0_f32.to_radians(),
+                ..default()
+            }),
+            Transform::from_xyz(0.0, 10.0, 20.0).with_rotation(spectator_rotation(camera.yaw, camera.pitch)),
+            GlobalTransform::default(),
+            camera,
+        ))
+        .id()
+}
+
+/// Fly the free camera or follow a player's first-person view (client-side)
+pub fn update_spectator_camera(
+    time: Res<Time>,
+    keys: Res<ButtonInput<KeyCode>>,
+    mut mouse_motion: MessageReader<MouseMotion>,
+    mut cameras: Query<(&mut SpectatorCamera, &mut Transform)>,
+    players: Query<(&Player, &PlayerRotation, &Transform), Without<SpectatorCamera>>,
+) {
+    let Ok((mut camera, mut transform)) = cameras.single_mut() else {
+        mouse_motion.clear();
+        return;
+    };
+
+    // F toggles between free and follow, Tab cycles through players in id order
+    if keys.just_pressed(KeyCode::KeyF) || keys.just_pressed(KeyCode::Tab) {
+        let mut player_ids: Vec<u64> = players.iter().map(|(player, _, _)| player.id).collect();
+        player_ids.sort_unstable();
+
+        let next_player = match camera.mode {
+            SpectatorMode::Follow(current) => player_ids.iter().find(|id| **id > current),
+            SpectatorMode::Free => None,
+        }
+        .or(player_ids.first());
+
+        camera.mode = match (camera.mode, next_player) {
+            (SpectatorMode::Follow(_), _) if keys.just_pressed(KeyCode::KeyF) => SpectatorMode::Free,
+            (_, Some(id)) => SpectatorMode::Follow(*id),
+            (_, None) => SpectatorMode::Free,
+        };
+    }
+
+    match camera.mode {
+        SpectatorMode::Free => {
+            for ev in mouse_motion.read() {
+                camera.yaw -= ev.delta.x * camera.sensitivity;
+                camera.pitch -= ev.delta.y * camera.sensitivity;
+                camera.pitch = camera.pitch.clamp(-PITCH_LOCK, PITCH_LOCK);
+            }
+            transform.rotation = spectator_rotation(camera.yaw, camera.pitch);
+
+            let mut direction = Vec3::ZERO;
+            if keys.pressed(KeyCode::KeyW) {
+                direction += *transform.forward();
+            }
+            if keys.pressed(KeyCode::KeyS) {
+                direction -= *transform.forward();
+            }
+            if keys.pressed(KeyCode::KeyD) {
+                direction += *transform.right();
+            }
+            if keys.pressed(KeyCode::KeyA) {
+                direction -= *transform.right();
+            }
+            if keys.pressed(KeyCode::Space) {
+                direction += Vec3::Y;
+            }
+            if keys.pressed(KeyCode::ShiftLeft) {
+                direction -= Vec3::Y;
+            }
+
+            transform.translation += direction.normalize_or_zero() * camera.fly_speed * time.delta_secs();
+        }
+        SpectatorMode::Follow(player_id) => {
+            mouse_motion.clear();
+
+            let Some((_, rotation, player_transform)) = players.iter().find(|(player, _, _)| player.id == player_id) else {
+                // Followed player left, keep the current view as a free camera
+                camera.mode = SpectatorMode::Free;
+                return;
+            };
+
+            // Look through the player's eyes using their replicated rotation
+            camera.yaw = rotation.yaw;
+            camera.pitch = rotation.pitch;
+            transform.translation = player_transform.translation + Vec3::Y * EYE_HEIGHT;
+            transform.rotation = spectator_rotation(rotation.yaw, rotation.pitch);
+        }
+    }
+}
+
+fn spectator_rotation(yaw: f32, pitch: f32) -> Quat {
+    let y_quat = Quat::from_axis_angle(Vec3::Y, yaw.to_radians());
+    let x_quat = Quat::from_axis_angle(Vec3::X, pitch.to_radians());
+    y_quat * x_quat
+}
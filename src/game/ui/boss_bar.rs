@@ -0,0 +1,91 @@
+use bevy::prelude::*;
+
+use crate::network::protocol::{Boss, Enemy, Health};
+
+/// Marker component for the boss bar container
+#[derive(Component)]
+pub struct BossBar;
+
+/// Marker component for the boss name and phase text
+#[derive(Component)]
+pub struct BossBarText;
+
+/// Marker component for the part of the bar that shrinks with the boss's health
+#[derive(Component)]
+pub struct BossBarFill;
+
+/// Sets up the hidden boss health bar at the top of the screen
+pub fn setup_boss_bar(mut commands: Commands) {
+    commands
+        .spawn((
+            Node {
+                position_type: PositionType::Absolute,
+                top: Val::Px(40.0),
+                left: Val::Percent(25.0),
+                width: Val::Percent(50.0),
+                flex_direction: FlexDirection::Column,
+                align_items: AlignItems::Center,
+                row_gap: Val::Px(4.0),
+                ..default()
+            },
+            Visibility::Hidden,
+            BossBar,
+        ))
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new(""),
+                TextFont {
+                    font_size: 20.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                BossBarText,
+            ));
+            parent
+                .spawn((
+                    Node {
+                        width: Val::Percent(100.0),
+                        height: Val::Px(14.0),
+                        ..default()
+                    },
+                    BackgroundColor(Color::srgba(0.0, 0.0, 0.0, 0.6)),
+                ))
+                .with_children(|bar| {
+                    bar.spawn((
+                        Node {
+                            width: Val::Percent(100.0),
+                            height: Val::Percent(100.0),
+                            ..default()
+                        },
+                        BackgroundColor(Color::srgb(0.8, 0.1, 0.1)),
+                        BossBarFill,
+                    ));
+                });
+        });
+}
+
+/// Shows the health and phase of the living boss, hidden while there is none
+pub fn update_boss_bar(
+    bosses: Query<(&Boss, &Health), With<Enemy>>,
+    mut bars: Query<&mut Visibility, With<BossBar>>,
+    mut texts: Query<&mut Text, With<BossBarText>>,
+    mut fills: Query<&mut Node, With<BossBarFill>>,
+) {
+    let (Ok(mut visibility), Ok(mut text), Ok(mut fill)) = (bars.single_mut(), texts.single_mut(), fills.single_mut())
+    else {
+        return;
+    };
+
+    let Some((boss, health)) = bosses.iter().find(|(_, health)| health.current > 0.0) else {
+        visibility.set_if_neq(Visibility::Hidden);
+        return;
+    };
+
+    visibility.set_if_neq(Visibility::Inherited);
+    let mut name = boss.name.clone();
+    if let Some(first) = name.get_mut(0..1) {
+        first.make_ascii_uppercase();
+    }
+    text.0 = format!("{} - Phase {}", name, boss.phase + 1);
+    fill.width = Val::Percent((health.current / health.max).clamp(0.0, 1.0) * 100.0);
+}
@@ -0,0 +1,62 @@
+use bevy::prelude::*;
+
+use crate::network::clock::ServerClock;
+use crate::network::protocol::MatchClock;
+
+/// Marker component for the match timer text
+#[derive(Component)]
+pub struct MatchTimerText;
+
+/// Sets up the match timer text at the top center of the screen
+pub fn setup_match_timer(mut commands: Commands) {
+    commands
+        .spawn(Node {
+            width: Val::Percent(100.0),
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            justify_content: JustifyContent::Center,
+            ..default()
+        })
+        .with_children(|parent| {
+            parent.spawn((
+                Text::new("--:--"),
+                TextFont {
+                    font_size: 28.0,
+                    ..default()
+                },
+                TextColor(Color::WHITE),
+                MatchTimerText,
+            ));
+        });
+}
+
+/// Shows the time left in the match using the synchronized server clock
+pub fn update_match_timer(
+    clock: Res<ServerClock>,
+    time: Res<Time<Real>>,
+    match_clocks: Query<&MatchClock>,
+    mut texts: Query<&mut Text, With<MatchTimerText>>,
+) {
+    let Ok(mut text) = texts.single_mut() else {
+        return;
+    };
+
+    let Ok(match_clock) = match_clocks.single() else {
+        text.0 = "--:--".to_string();
+        return;
+    };
+
+    if !clock.is_synced() {
+        text.0 = "--:--".to_string();
+        return;
+    }
+
+    let server_time = clock.server_time(time.elapsed_secs_f64());
+    let remaining = (match_clock.started_at + match_clock.duration as f64 - server_time).max(0.0) as u32;
+    text.0 = format!(
+        "{:02}:{:02}  ({:.0} ms)",
+        remaining / 60,
+        remaining % 60,
+        clock.rtt() * 1000.0
+    );
+}
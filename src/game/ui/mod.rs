@@ -1,3 +1,11 @@
+pub mod boss_bar;
 pub mod crosshair;
+pub mod match_timer;
+pub mod playback_hud;
+pub mod wave_hud;
 
+pub use boss_bar::{setup_boss_bar, update_boss_bar};
 pub use crosshair::setup_crosshair;
+pub use match_timer::{setup_match_timer, update_match_timer};
+pub use playback_hud::{setup_playback_hud, update_playback_hud};
+pub use wave_hud::{setup_wave_hud, update_wave_hud};
@@ -0,0 +1,41 @@
+use bevy::prelude::*;
+
+use crate::network::demo::DemoPlayback;
+
+/// Marker component for the demo playback status text
+#[derive(Component)]
+pub struct PlaybackHudText;
+
+/// Sets up the playback status text in the bottom left corner
+pub fn setup_playback_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 20.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            bottom: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        PlaybackHudText,
+    ));
+}
+
+/// Shows playback position, speed and pause state
+pub fn update_playback_hud(playback: Res<DemoPlayback>, mut texts: Query<&mut Text, With<PlaybackHudText>>) {
+    let Ok(mut text) = texts.single_mut() else {
+        return;
+    };
+
+    text.0 = format!(
+        "{:.1}s / {:.1}s  x{}{}",
+        playback.time,
+        playback.duration(),
+        playback.speed,
+        if playback.paused { "  PAUSED" } else { "" }
+    );
+}
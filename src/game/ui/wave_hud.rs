@@ -0,0 +1,42 @@
+use bevy::prelude::*;
+
+use crate::network::protocol::WaveInfo;
+
+/// Marker component for the wave status text
+#[derive(Component)]
+pub struct WaveHudText;
+
+/// Sets up the wave status text in the top left corner
+pub fn setup_wave_hud(mut commands: Commands) {
+    commands.spawn((
+        Text::new(""),
+        TextFont {
+            font_size: 22.0,
+            ..default()
+        },
+        TextColor(Color::WHITE),
+        Node {
+            position_type: PositionType::Absolute,
+            top: Val::Px(10.0),
+            left: Val::Px(10.0),
+            ..default()
+        },
+        WaveHudText,
+    ));
+}
+
+/// Shows the current wave and either the enemies left or the countdown to the next wave
+pub fn update_wave_hud(
+    wave_infos: Query<&WaveInfo, Changed<WaveInfo>>,
+    mut texts: Query<&mut Text, With<WaveHudText>>,
+) {
+    let (Ok(wave_info), Ok(mut text)) = (wave_infos.single(), texts.single_mut()) else {
+        return;
+    };
+
+    text.0 = if wave_info.countdown > 0 {
+        format!("Wave {} in {}s", wave_info.wave + 1, wave_info.countdown)
+    } else {
+        format!("Wave {} - {} enemies left", wave_info.wave, wave_info.enemies_remaining)
+    };
+}
@@ -0,0 +1,123 @@
+use bevy::prelude::*;
+
+/// Tunables for the wave director (server-only)
+#[derive(Resource)]
+pub struct WaveConfig {
+    /// Groups spawn at these points in turn
+    pub spawn_points: Vec<Vec3>,
+    /// Groups patrol the level route closest to their spawn point if it passes within this distance,
+    /// otherwise a square around the spawn point
+    pub patrol_route_distance: f32,
+    /// Seconds between a cleared wave (or server start) and the next wave
+    pub intermission: f32,
+    /// Seconds between two groups of the same wave
+    pub group_interval: f32,
+    /// Enemies per group
+    pub group_size: u32,
+    /// Enemies in the first wave with one player
+    pub base_enemies: u32,
+    /// Extra enemies for each wave after the first
+    pub enemies_per_wave: u32,
+    /// Extra enemies for each player beyond the first
+    pub enemies_per_extra_player: u32,
+    /// Archetype most enemies are spawned from
+    pub archetype: String,
+    /// Archetype used for every third enemy from `heavy_from_wave` on,
+    /// the pacing director's special mix makes them more or less common
+    pub heavy_archetype: String,
+    pub heavy_from_wave: u32,
+    /// Archetype used for every third enemy, alternating with the heavies, from `ranged_from_wave` on
+    pub ranged_archetype: String,
+    pub ranged_from_wave: u32,
+    /// Archetype of every third group from `flying_from_wave` on, flyers spawn as a whole flock
+    pub flying_archetype: String,
+    pub flying_from_wave: u32,
+    /// Boss spawned with the first group of every `boss_every` waves, 0 disables bosses
+    pub boss_archetype: String,
+    pub boss_every: u32,
+    /// Stat growth per wave after the first (0.1 = +10% per wave)
+    pub health_growth: f32,
+    pub damage_growth: f32,
+    pub speed_growth: f32,
+    /// Speed multiplier cap, pacing aggression included, so late waves stay slower than players
+    pub max_speed_scale: f32,
+}
+
+impl Default for WaveConfig {
+    fn default() -> Self {
+        Self {
+            spawn_points: vec![
+                Vec3::new(10.0, 1.0, 10.0),
+                Vec3::new(-15.0, 1.0, 15.0),
+                Vec3::new(15.0, 1.0, -15.0),
+                Vec3::new(-15.0, 1.0, -15.0),
+            ],
+            patrol_route_distance: 25.0,
+            intermission: 10.0,
+            group_interval: 2.0,
+            group_size: 3,
+            base_enemies: 3,
+            enemies_per_wave: 2,
+            enemies_per_extra_player: 2,
+            archetype: "grunt".to_string(),
+            heavy_archetype: "brute".to_string(),
+            heavy_from_wave: 2,
+            ranged_archetype: "gunner".to_string(),
+            ranged_from_wave: 3,
+            flying_archetype: "drone".to_string(),
+            flying_from_wave: 4,
+            boss_archetype: "warden".to_string(),
+            boss_every: 5,
+            health_growth: 0.15,
+            damage_growth: 0.1,
+            speed_growth: 0.05,
+            max_speed_scale: 1.2,
+        }
+    }
+}
+
+/// What the wave director is doing right now (server-only)
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum WavePhase {
+    /// Counting down to the next wave
+    Intermission { remaining: f32 },
+    /// Spawning the groups of the current wave
+    Spawning { left_to_spawn: u32, next_group_in: f32 },
+    /// Everything spawned, waiting for the wave to be cleared
+    InProgress,
+}
+
+/// Wave director state (server-only)
+#[derive(Resource)]
+pub struct WaveDirector {
+    /// Current wave, 0 before the first wave
+    pub wave: u32,
+    pub phase: WavePhase,
+    pub next_enemy_id: u32,
+    pub next_spawn_point: usize,
+    /// Enemies spawned so far in the current wave, picks the archetype
+    pub spawned_this_wave: u32,
+    /// Groups spawned so far in the current wave, picks flocks of flyers
+    pub groups_this_wave: u32,
+    /// Builds up with every enemy spawned, a heavy or ranged enemy is spawned each time it reaches one
+    pub special_credit: f32,
+    /// Heavy and ranged enemies spawned so far in the current wave, alternates between them
+    pub specials_this_wave: u32,
+}
+
+impl WaveDirector {
+    pub fn new(config: &WaveConfig) -> Self {
+        Self {
+            wave: 0,
+            phase: WavePhase::Intermission {
+                remaining: config.intermission,
+            },
+            next_enemy_id: 1,
+            next_spawn_point: 0,
+            spawned_this_wave: 0,
+            groups_this_wave: 0,
+            special_credit: 0.0,
+            specials_this_wave: 0,
+        }
+    }
+}
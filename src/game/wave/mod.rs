@@ -0,0 +1,7 @@
+pub mod components;
+pub mod pacing;
+pub mod systems;
+
+pub use components::{WaveConfig, WaveDirector};
+pub use pacing::{pacing_director_system, track_pacing_signals, Pacing, PacingConfig};
+pub use systems::{setup_wave_director, wave_director_system};
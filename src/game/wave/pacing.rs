@@ -0,0 +1,212 @@
+use bevy::prelude::*;
+use std::collections::HashMap;
+
+use crate::game::enemy::components::{EnemyHit, EnemyState, Gunshot};
+use crate::network::protocol::{Enemy, Health, Player};
+
+/// Bounds and thresholds of the pacing director (server-only)
+#[derive(Resource)]
+pub struct PacingConfig {
+    /// Seconds between pacing decisions, each looks at what happened since the previous one
+    pub evaluate_interval: f32,
+    /// Players are struggling when they lose more than this fraction of a life each, or anyone dies
+    pub struggling_damage: f32,
+    /// Players are cruising when they lose less than this fraction of a life each
+    /// while hitting at least `cruising_accuracy` of their shots or killing `cruising_kills` enemies each
+    pub cruising_damage: f32,
+    pub cruising_accuracy: f32,
+    pub cruising_kills: f32,
+    /// Seconds of enemies alive without anyone taking or dealing damage before the director steps up anyway
+    pub lull_time: f32,
+    /// How far one decision moves each multiplier
+    pub step: f32,
+    /// Multiplier on wave size and how fast groups follow each other
+    pub min_spawn_rate: f32,
+    pub max_spawn_rate: f32,
+    /// Multiplier on how often heavy and ranged enemies replace regular ones
+    pub min_special_mix: f32,
+    pub max_special_mix: f32,
+    /// Multiplier on the speed and sight range of new enemies
+    pub min_aggression: f32,
+    pub max_aggression: f32,
+}
+
+impl Default for PacingConfig {
+    fn default() -> Self {
+        Self {
+            evaluate_interval: 10.0,
+            struggling_damage: 0.5,
+            cruising_damage: 0.1,
+            cruising_accuracy: 0.5,
+            cruising_kills: 2.0,
+            lull_time: 20.0,
+            step: 0.1,
+            min_spawn_rate: 0.5,
+            max_spawn_rate: 1.5,
+            min_special_mix: 0.5,
+            max_special_mix: 1.5,
+            min_aggression: 0.8,
+            max_aggression: 1.25,
+        }
+    }
+}
+
+/// Current pacing multipliers and what players went through since the last decision (server-only)
+#[derive(Resource)]
+pub struct Pacing {
+    pub spawn_rate: f32,
+    pub special_mix: f32,
+    pub aggression: f32,
+    pub damage_taken: f32,
+    pub deaths: u32,
+    pub shots: u32,
+    pub hits: u32,
+    pub kills: u32,
+    /// Seconds with enemies alive since anyone took or dealt damage
+    pub since_engagement: f32,
+    since_evaluate: f32,
+}
+
+impl Default for Pacing {
+    fn default() -> Self {
+        Self {
+            spawn_rate: 1.0,
+            special_mix: 1.0,
+            aggression: 1.0,
+            damage_taken: 0.0,
+            deaths: 0,
+            shots: 0,
+            hits: 0,
+            kills: 0,
+            since_engagement: 0.0,
+            since_evaluate: 0.0,
+        }
+    }
+}
+
+impl Pacing {
+    fn accuracy(&self) -> f32 {
+        if self.shots == 0 {
+            0.0
+        } else {
+            self.hits as f32 / self.shots as f32
+        }
+    }
+
+    fn clear_window(&mut self) {
+        self.damage_taken = 0.0;
+        self.deaths = 0;
+        self.shots = 0;
+        self.hits = 0;
+        self.kills = 0;
+        self.since_evaluate = 0.0;
+    }
+}
+
+/// How the last stretch of the session went for the players
+#[derive(Debug, Clone, Copy, PartialEq)]
+enum PacingVerdict {
+    Struggling,
+    Cruising,
+    Lull,
+    Steady,
+}
+
+/// Count damage and deaths of players, shots fired and enemies hit and killed (server-only)
+pub fn track_pacing_signals(
+    mut pacing: ResMut<Pacing>,
+    mut gunshots: MessageReader<Gunshot>,
+    mut enemy_hits: MessageReader<EnemyHit>,
+    mut last_health: Local<HashMap<Entity, f32>>,
+    players: Query<(Entity, &Health), With<Player>>,
+    enemies: Query<&EnemyState, With<Enemy>>,
+    time: Res<Time>,
+) {
+    let mut engaged = false;
+    pacing.shots += gunshots.read().count() as u32;
+    for hit in enemy_hits.read() {
+        pacing.hits += 1;
+        pacing.kills += hit.killed as u32;
+        engaged = true;
+    }
+
+    // Health going down is damage from any source, going up is a respawn
+    last_health.retain(|entity, _| players.contains(*entity));
+    for (entity, health) in players.iter() {
+        let previous = last_health.insert(entity, health.current).unwrap_or(health.current);
+        if health.current < previous {
+            pacing.damage_taken += previous - health.current;
+            engaged = true;
+            if health.current <= 0.0 {
+                pacing.deaths += 1;
+            }
+        }
+    }
+
+    // Quiet intermissions between waves aren't a lull
+    let enemies_alive = enemies.iter().any(|state| *state != EnemyState::Dead);
+    if engaged || !enemies_alive {
+        pacing.since_engagement = 0.0;
+    } else {
+        pacing.since_engagement += time.delta_secs();
+    }
+}
+
+/// Every `evaluate_interval` ease off when players are struggling and push harder when they are
+/// cruising or nothing is happening, within the configured bounds (server-only)
+pub fn pacing_director_system(
+    config: Res<PacingConfig>,
+    mut pacing: ResMut<Pacing>,
+    players: Query<&Health, With<Player>>,
+    time: Res<Time>,
+) {
+    let player_count = players.iter().count();
+    if player_count == 0 {
+        pacing.clear_window();
+        return;
+    }
+    pacing.since_evaluate += time.delta_secs();
+    if pacing.since_evaluate < config.evaluate_interval {
+        return;
+    }
+
+    // Damage in lives lost per player, so the thresholds don't depend on player count or max health
+    let max_health = players.iter().map(|health| health.max).sum::<f32>() / player_count as f32;
+    let damage_per_player = pacing.damage_taken / max_health.max(1.0) / player_count as f32;
+    let kills_per_player = pacing.kills as f32 / player_count as f32;
+
+    let verdict = if pacing.deaths > 0 || damage_per_player > config.struggling_damage {
+        PacingVerdict::Struggling
+    } else if pacing.since_engagement >= config.lull_time {
+        PacingVerdict::Lull
+    } else if damage_per_player < config.cruising_damage
+        && (pacing.accuracy() >= config.cruising_accuracy || kills_per_player >= config.cruising_kills)
+    {
+        PacingVerdict::Cruising
+    } else {
+        PacingVerdict::Steady
+    };
+
+    let step = match verdict {
+        PacingVerdict::Struggling => -config.step,
+        PacingVerdict::Cruising | PacingVerdict::Lull => config.step,
+        PacingVerdict::Steady => 0.0,
+    };
+    pacing.spawn_rate = (pacing.spawn_rate + step).clamp(config.min_spawn_rate, config.max_spawn_rate);
+    pacing.special_mix = (pacing.special_mix + step).clamp(config.min_special_mix, config.max_special_mix);
+    pacing.aggression = (pacing.aggression + step).clamp(config.min_aggression, config.max_aggression);
+
+    println!(
+        "[SERVER] Pacing: {:?} ({:.2} lives lost per player, {} deaths, {} kills, {:.0}% accuracy, {:.0}s since engagement) -> spawn rate {:.2}, special mix {:.2}, aggression {:.2}",
+        verdict,
+        damage_per_player,
+        pacing.deaths,
+        pacing.kills,
+        pacing.accuracy() * 100.0,
+        pacing.since_engagement,
+        pacing.spawn_rate,
+        pacing.special_mix,
+        pacing.aggression
+    );
+    pacing.clear_window();
+}
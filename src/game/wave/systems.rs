@@ -0,0 +1,240 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+use super::components::{WaveConfig, WaveDirector, WavePhase};
+use super::pacing::Pacing;
+use crate::game::enemy::{
+    archetype::{EnemyArchetype, EnemyArchetypes, EnemyScaling, EnemySpawn},
+    components::{resume_at_nearest_waypoint, EnemyState, PatrolData},
+    spawn_enemy,
+};
+use crate::game::world::level::{CurrentLevel, Level};
+use crate::network::protocol::{Enemy, Player, WaveInfo};
+
+/// Set up the wave director and its replicated status entity (server-side)
+pub fn setup_wave_director(mut commands: Commands, config: Res<WaveConfig>) {
+    let director = WaveDirector::new(&config);
+    commands.spawn((wave_info(&director, 0), Replicated));
+    commands.insert_resource(director);
+}
+
+/// Spawn waves of enemies over time, escalating with each cleared wave (server-side)
+pub fn wave_director_system(
+    mut commands: Commands,
+    config: Res<WaveConfig>,
+    mut director: ResMut<WaveDirector>,
+    pacing: Res<Pacing>,
+    archetype_assets: Res<Assets<EnemyArchetype>>,
+    archetypes: Res<EnemyArchetypes>,
+    current_level: Option<Res<CurrentLevel>>,
+    levels: Res<Assets<Level>>,
+    mut wave_infos: Query<&mut WaveInfo>,
+    enemies: Query<&EnemyState, With<Enemy>>,
+    players: Query<&Player>,
+    time: Res<Time>,
+) {
+    let player_count = players.iter().count() as u32;
+    let alive_enemies = enemies.iter().filter(|state| **state != EnemyState::Dead).count() as u32;
+
+    match director.phase {
+        WavePhase::Intermission { remaining } => {
+            // Hold the countdown while the arena is empty or the archetypes are still loading
+            if player_count == 0 || !archetypes.is_loaded() {
+                director.phase = WavePhase::Intermission {
+                    remaining: config.intermission,
+                };
+            } else if remaining <= 0.0 {
+                director.wave += 1;
+                director.spawned_this_wave = 0;
+                director.groups_this_wave = 0;
+                director.special_credit = 0.0;
+                director.specials_this_wave = 0;
+
+                let wave_size = wave_size(&config, director.wave, player_count, pacing.spawn_rate);
+                director.phase = WavePhase::Spawning {
+                    left_to_spawn: wave_size,
+                    next_group_in: 0.0,
+                };
+                println!("[SERVER] Wave {} starting: {} enemies for {} players", director.wave, wave_size, player_count);
+            } else {
+                director.phase = WavePhase::Intermission {
+                    remaining: remaining - time.delta_secs(),
+                };
+            }
+        }
+        WavePhase::Spawning {
+            left_to_spawn,
+            next_group_in,
+        } => {
+            if next_group_in > 0.0 {
+                director.phase = WavePhase::Spawning {
+                    left_to_spawn,
+                    next_group_in: next_group_in - time.delta_secs(),
+                };
+            } else {
+                let group_size = left_to_spawn.min(config.group_size);
+                let level = current_level.as_ref().and_then(|current| levels.get(&current.0));
+                spawn_group(
+                    &mut commands,
+                    &config,
+                    &mut director,
+                    &pacing,
+                    level,
+                    &archetype_assets,
+                    &archetypes,
+                    group_size,
+                );
+
+                director.phase = if left_to_spawn > group_size {
+                    WavePhase::Spawning {
+                        left_to_spawn: left_to_spawn - group_size,
+                        next_group_in: config.group_interval / pacing.spawn_rate,
+                    }
+                } else {
+                    WavePhase::InProgress
+                };
+            }
+        }
+        WavePhase::InProgress => {
+            if alive_enemies == 0 {
+                println!("[SERVER] Wave {} cleared", director.wave);
+                director.phase = WavePhase::Intermission {
+                    remaining: config.intermission,
+                };
+            }
+        }
+    }
+
+    let info = wave_info(&director, alive_enemies);
+    for mut wave_info in wave_infos.iter_mut() {
+        // Only touch the component when something changed so it isn't re-sent every tick
+        if *wave_info != info {
+            *wave_info = info.clone();
+        }
+    }
+}
+
+/// Enemies in a wave, grows with the wave number and the number of players, scaled by the pacing director
+fn wave_size(config: &WaveConfig, wave: u32, player_count: u32, spawn_rate: f32) -> u32 {
+    let size = config.base_enemies
+        + config.enemies_per_wave * (wave - 1)
+        + config.enemies_per_extra_player * player_count.saturating_sub(1);
+    ((size as f32 * spawn_rate).round() as u32).max(1)
+}
+
+/// Spawn one group around the next spawn point, with stats scaled to the current wave and the pacing
+fn spawn_group(
+    commands: &mut Commands,
+    config: &WaveConfig,
+    director: &mut WaveDirector,
+    pacing: &Pacing,
+    level: Option<&Level>,
+    archetype_assets: &Assets<EnemyArchetype>,
+    archetypes: &EnemyArchetypes,
+    group_size: u32,
+) {
+    let Some(spawn_point) = config
+        .spawn_points
+        .get(director.next_spawn_point % config.spawn_points.len().max(1))
+        .copied()
+    else {
+        warn!("Wave config has no spawn points");
+        return;
+    };
+    director.next_spawn_point += 1;
+
+    // The whole group walks the same route, joining it at the point closest to the spawn point
+    let route = level.and_then(|level| level.nearest_patrol_route(spawn_point, config.patrol_route_distance));
+    let patrol = || match route {
+        Some(route) => {
+            let mut patrol = route.patrol();
+            resume_at_nearest_waypoint(&mut patrol, spawn_point);
+            patrol
+        }
+        None => PatrolData::new(spawn_point, 5.0),
+    };
+
+    let growth = (director.wave - 1) as f32;
+    let scaling = EnemyScaling {
+        health: 1.0 + config.health_growth * growth,
+        damage: 1.0 + config.damage_growth * growth,
+        speed: ((1.0 + config.speed_growth * growth) * pacing.aggression).min(config.max_speed_scale),
+        range: pacing.aggression,
+    };
+
+    let flock = director.wave >= config.flying_from_wave && director.groups_this_wave % 3 == 2;
+    let boss = config.boss_every > 0 && director.wave % config.boss_every == 0 && director.groups_this_wave == 0;
+    director.groups_this_wave += 1;
+
+    // The boss leads the first group of a boss wave, on top of the wave's enemy count
+    if boss {
+        match archetypes.get(archetype_assets, &config.boss_archetype) {
+            Some(archetype) => {
+                let spawn = EnemySpawn::new(archetype, scaling);
+                spawn_enemy(commands, director.next_enemy_id, &spawn, spawn_point, patrol());
+                director.next_enemy_id += 1;
+                println!("[SERVER] Boss '{}' joins wave {}", config.boss_archetype, director.wave);
+            }
+            None => warn!("Unknown enemy archetype '{}'", config.boss_archetype),
+        }
+    }
+
+    for index in 0..group_size {
+        // Spread the group in a small circle so flocking has something to separate
+        let angle = index as f32 * std::f32::consts::TAU / group_size as f32;
+        let position = spawn_point + Vec3::new(angle.cos(), 0.0, angle.sin()) * 2.0;
+
+        let name = if flock {
+            &config.flying_archetype
+        } else {
+            special_archetype(config, director, pacing).unwrap_or(&config.archetype)
+        };
+        let Some(archetype) = archetypes.get(archetype_assets, name) else {
+            warn!("Unknown enemy archetype '{}'", name);
+            director.spawned_this_wave += 1;
+            continue;
+        };
+        let spawn = EnemySpawn::new(archetype, scaling);
+
+        spawn_enemy(commands, director.next_enemy_id, &spawn, position, patrol());
+        director.next_enemy_id += 1;
+        director.spawned_this_wave += 1;
+    }
+}
+
+/// Heavy or ranged archetype if this enemy should be one, each unlocked kind takes every third enemy
+/// at a special mix of 1, alternating between them
+fn special_archetype<'a>(config: &'a WaveConfig, director: &mut WaveDirector, pacing: &Pacing) -> Option<&'a String> {
+    let mut kinds = Vec::new();
+    if director.wave >= config.heavy_from_wave {
+        kinds.push(&config.heavy_archetype);
+    }
+    if director.wave >= config.ranged_from_wave {
+        kinds.push(&config.ranged_archetype);
+    }
+    if kinds.is_empty() {
+        return None;
+    }
+
+    director.special_credit += kinds.len() as f32 / 3.0 * pacing.special_mix;
+    if director.special_credit < 1.0 {
+        return None;
+    }
+    director.special_credit -= 1.0;
+    let kind = kinds[director.specials_this_wave as usize % kinds.len()];
+    director.specials_this_wave += 1;
+    Some(kind)
+}
+
+fn wave_info(director: &WaveDirector, enemies_remaining: u32) -> WaveInfo {
+    let countdown = match director.phase {
+        WavePhase::Intermission { remaining } => remaining.max(0.0).ceil() as u32,
+        _ => 0,
+    };
+
+    WaveInfo {
+        wave: director.wave,
+        countdown,
+        enemies_remaining,
+    }
+}
@@ -0,0 +1,221 @@
+use bevy::{
+    asset::{io::Reader, AssetLoader, LoadContext},
+    prelude::*,
+};
+use bevy_rapier3d::prelude::*;
+use serde::Deserialize;
+
+use crate::game::enemy::components::{PatrolData, PatrolMode};
+use crate::game::ron_asset::{read_ron, RonAssetError};
+
+/// Level loaded on every side, the server needs its obstacles for physics and the navmesh
+const LEVEL_PATH: &str = "levels/arena.level.ron";
+
+/// Color of the level's obstacle boxes
+const OBSTACLE_COLOR: Color = Color::srgb(0.55, 0.5, 0.45);
+
+/// Static level geometry loaded from a `.level.ron` asset file
+#[derive(Asset, TypePath, Deserialize, Clone)]
+pub struct Level {
+    #[serde(default)]
+    pub obstacles: Vec<LevelObstacle>,
+    #[serde(default)]
+    pub patrol_routes: Vec<LevelPatrolRoute>,
+    /// Cover spots on top of the ones generated along the obstacles
+    #[serde(default)]
+    pub cover_points: Vec<LevelCoverPoint>,
+}
+
+impl Level {
+    /// Route passing closest to `position`, if any of its points is within `max_distance`
+    pub fn nearest_patrol_route(&self, position: Vec3, max_distance: f32) -> Option<&LevelPatrolRoute> {
+        self.patrol_routes
+            .iter()
+            .filter_map(|route| route.distance_to(position).map(|distance| (route, distance)))
+            .filter(|(_, distance)| *distance <= max_distance)
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(route, _)| route)
+    }
+}
+
+/// Axis-aligned box blocking movement and line of sight
+#[derive(Deserialize, Clone)]
+pub struct LevelObstacle {
+    pub center: (f32, f32, f32),
+    pub half_extents: (f32, f32, f32),
+}
+
+/// Spot where an enemy can hide, e.g. a corner the generated cover points miss
+#[derive(Deserialize, Clone)]
+pub struct LevelCoverPoint {
+    pub position: (f32, f32, f32),
+}
+
+/// Named route enemies spawned near it patrol instead of the default square
+#[derive(Deserialize, Clone)]
+pub struct LevelPatrolRoute {
+    pub name: String,
+    #[serde(default)]
+    pub mode: PatrolMode,
+    /// Walk a Catmull-Rom spline through the points instead of straight lines between them
+    #[serde(default)]
+    pub smooth: bool,
+    pub points: Vec<LevelPatrolPoint>,
+}
+
+#[derive(Deserialize, Clone)]
+pub struct LevelPatrolPoint {
+    pub position: (f32, f32, f32),
+    /// Seconds to stand at the point before walking on
+    #[serde(default)]
+    pub wait: f32,
+}
+
+impl LevelPatrolRoute {
+    /// Distance from `position` to the closest point of the route, none for a route without points
+    fn distance_to(&self, position: Vec3) -> Option<f32> {
+        self.points
+            .iter()
+            .map(|point| Vec3::from(point.position).distance(position))
+            .min_by(|a, b| a.total_cmp(b))
+    }
+
+    /// Patrol along this route
+    pub fn patrol(&self) -> PatrolData {
+        let points: Vec<(Vec3, f32)> = self
+            .points
+            .iter()
+            .map(|point| (Vec3::from(point.position), point.wait))
+            .collect();
+        let points = if self.smooth {
+            smooth_route(&points, self.mode == PatrolMode::Loop)
+        } else {
+            points
+        };
+        PatrolData::route(&self.name, points, self.mode)
+    }
+}
+
+/// Spline samples apart along a smoothed route
+const ROUTE_SAMPLE_SPACING: f32 = 2.0;
+
+/// Sample a Catmull-Rom spline through the authored points, which keep their waits
+fn smooth_route(points: &[(Vec3, f32)], closed: bool) -> Vec<(Vec3, f32)> {
+    if points.len() < 3 {
+        return points.to_vec();
+    }
+
+    let count = points.len() as isize;
+    let point = |index: isize| {
+        let index = if closed { index.rem_euclid(count) } else { index.clamp(0, count - 1) };
+        points[index as usize].0
+    };
+    let segments = if closed { count } else { count - 1 };
+
+    let mut samples = Vec::new();
+    for segment in 0..segments {
+        let (p0, p1, p2, p3) = (point(segment - 1), point(segment), point(segment + 1), point(segment + 2));
+        samples.push(points[segment as usize]);
+        let steps = (p1.distance(p2) / ROUTE_SAMPLE_SPACING).ceil().max(1.0) as usize;
+        for step in 1..steps {
+            let t = step as f32 / steps as f32;
+            let (t2, t3) = (t * t, t * t * t);
+            let position = 0.5
+                * (2.0 * p1
+                    + (p2 - p0) * t
+                    + (2.0 * p0 - 5.0 * p1 + 4.0 * p2 - p3) * t2
+                    + (3.0 * p1 - p0 - 3.0 * p2 + p3) * t3);
+            samples.push((position, 0.0));
+        }
+    }
+    if !closed {
+        samples.push(points[points.len() - 1]);
+    }
+    samples
+}
+
+#[derive(Default, TypePath)]
+pub struct LevelLoader;
+
+impl AssetLoader for LevelLoader {
+    type Asset = Level;
+    type Settings = ();
+    type Error = RonAssetError;
+
+    async fn load(
+        &self,
+        reader: &mut dyn Reader,
+        _settings: &(),
+        _load_context: &mut LoadContext<'_>,
+    ) -> Result<Self::Asset, Self::Error> {
+        read_ron(reader).await
+    }
+
+    fn extensions(&self) -> &[&str] {
+        &["level.ron"]
+    }
+}
+
+/// Handle of the level being played
+#[derive(Resource)]
+pub struct CurrentLevel(pub Handle<Level>);
+
+/// Inserted once the level colliders have been spawned
+#[derive(Resource)]
+pub struct LevelReady;
+
+/// Marks a static collider spawned from the level file
+#[derive(Component)]
+pub struct LevelGeometry;
+
+pub fn load_level(mut commands: Commands, asset_server: Res<AssetServer>) {
+    commands.insert_resource(CurrentLevel(asset_server.load(LEVEL_PATH)));
+}
+
+/// Spawn the level's static colliders once the file has loaded,
+/// with a box mesh where rendering is available so players see what they collide with
+pub fn spawn_level_geometry(
+    mut commands: Commands,
+    current_level: Res<CurrentLevel>,
+    levels: Res<Assets<Level>>,
+    ready: Option<Res<LevelReady>>,
+    meshes: Option<ResMut<Assets<Mesh>>>,
+    materials: Option<ResMut<Assets<StandardMaterial>>>,
+) {
+    if ready.is_some() {
+        return;
+    }
+    let Some(level) = levels.get(&current_level.0) else {
+        return;
+    };
+
+    // The headless server has no render assets
+    let mut render = meshes.zip(materials).map(|(meshes, mut materials)| {
+        let material = materials.add(StandardMaterial {
+            base_color: OBSTACLE_COLOR,
+            ..default()
+        });
+        (meshes, material)
+    });
+
+    for obstacle in &level.obstacles {
+        let (x, y, z) = obstacle.center;
+        let (hx, hy, hz) = obstacle.half_extents;
+        let mut entity = commands.spawn((
+            LevelGeometry,
+            Transform::from_xyz(x, y, z),
+            GlobalTransform::default(),
+            RigidBody::Fixed,
+            Collider::cuboid(hx, hy, hz),
+        ));
+        if let Some((meshes, material)) = render.as_mut() {
+            entity.insert((
+                Mesh3d(meshes.add(Cuboid::new(hx * 2.0, hy * 2.0, hz * 2.0))),
+                MeshMaterial3d(material.clone()),
+            ));
+        }
+    }
+
+    commands.insert_resource(LevelReady);
+    println!("[SETUP] Level loaded with {} obstacles and {} patrol routes", level.obstacles.len(), level.patrol_routes.len());
+}
@@ -1,4 +1,6 @@
+pub mod level;
 pub mod setup;
 pub mod state;
 
+pub use level::{load_level, spawn_level_geometry, Level, LevelLoader, LevelReady};
 pub use setup::{init_server_state, setup_world, setup_server_world};
@@ -2,18 +2,33 @@ use bevy::prelude::*;
 use bevy::transform::TransformPlugin;
 use bevy_rapier3d::prelude::*;
 use bevy_replicon::prelude::*;
-use bevy_replicon_renet::RepliconRenetPlugins;
+use bevy_replicon_renet::{renet::RenetClient, RepliconRenetPlugins};
+use std::path::Path;
+use std::time::{Duration, Instant, SystemTime};
 
 mod game;
 mod network;
 
 use game::{
+    bench::{move_ai_bench_players, print_ai_bench_report, spawn_ai_bench, AiBench},
     cursor::CursorPlugin,
-    init_server_state, render_replicated_players, sync_remote_player_rotation, sync_player_position, setup_world, setup_server_world, spawn_players_system, handle_rotation_input, handle_movement_input, sync_transform_to_position, handle_shoot_events, respawn_players_system,
+    balance_bots, drive_bots, BotSettings,
+    init_server_state, render_replicated_players, spawn_host_player, sync_remote_player_rotation, sync_player_position, setup_world, setup_server_world, spawn_players_system, handle_rotation_input, handle_movement_input, sync_transform_to_position, handle_shoot_events, respawn_players_system,
     shooting::TracerPlugin,
-    spawn_enemies_system, enemy_fsm_system, enemy_flocking_system, enemy_movement_system, render_enemies_system, sync_enemy_position, sync_transform_to_enemy_position,
-    setup_crosshair,
+    render_projectiles_system, simulate_projectiles, sync_projectile_position,
+    pacing_director_system, setup_wave_director, track_pacing_signals, wave_director_system, Pacing, PacingConfig, WaveConfig,
+    build_navmesh, enemy_pathfinding_system, load_level, spawn_level_geometry, Level, LevelLoader, LevelReady, NavMesh, NavMeshConfig,
+    load_enemy_archetypes, reload_enemy_archetypes, EnemyArchetype, EnemyArchetypeLoader,
+    assign_squad_slots, flying_enemy_movement_system, SquadConfig,
+    build_cover_points, enemy_cover_system, CoverConfig, CoverPoints,
+    measure_enemy_ai, report_enemy_ai_lod, schedule_enemy_ai, AiLodConfig, AiLodMetrics, AiLodReport,
+    animate_area_attack_warnings, boss_ability_system, boss_phase_system, render_area_attack_warnings, resolve_area_attacks,
+    despawn_dead_enemies, render_dead_enemies, render_enemy_states, enemy_behavior_tree_system, dump_behavior_trees, BehaviorTreeDebug, enemy_perception_system, track_enemy_state_changes, log_enemy_state_changes, enemy_attack_system, enemy_flocking_system, enemy_movement_system, refresh_enemy_appearance, render_enemies_system, sync_enemy_position, sync_transform_to_enemy_position,
+    rebuild_spatial_index, SpatialIndex,
+    setup_boss_bar, update_boss_bar, setup_crosshair, setup_match_timer, update_match_timer, setup_playback_hud, update_playback_hud, setup_wave_hud, update_wave_hud,
+    handle_spectate_requests, manage_spectator_camera, send_spectate_toggle, spawn_spectator_camera, update_spectator_camera,
 };
+use game::enemy::components::{EnemyHit, EnemyStateChanged, Gunshot};
 use game::player::{
     camera_controller::update_camera_controller,
     input::PlayerInput,
@@ -22,9 +37,17 @@ use game::player::{
 };
 use network::{
     client_connection_system, server_connection_system, setup_client, setup_server, 
-    Player, PlayerPosition, PlayerRotation, Health, Enemy, EnemyPosition, PORT,
+    receive_clock_sync, respond_clock_sync, send_clock_sync, setup_match_clock, update_match_clock, ServerClock,
+    Player, PlayerPosition, PlayerRotation, Health, AreaAttackWarning, Boss, Enemy, EnemyAppearance, EnemyPosition, Projectile, Spectator, HOST_CLIENT_ID, PORT,
 };
-use network::protocol::{RotationInput, MovementInput, ShootEvent};
+use network::demo::{
+    demo_playback_controls, discard_demo_sent_messages, feed_demo_frames, read_demo, record_demo, start_demo_playback,
+    DemoPlayback, DemoRecorder,
+};
+use network::load_test::{
+    collect_load_test_metrics, drive_load_test_bot, print_load_test_report, BotScript, LoadTestBot, LoadTestMetrics,
+};
+use network::protocol::{EnemyState, RotationInput, MovementInput, ShootEvent, SpectateRequest, ClockSyncRequest, ClockSyncResponse, MatchClock, PlayerStats, WaveInfo, SERVER_TICK_RATE};
 
 fn main() {
     let args: Vec<String> = std::env::args().collect();
@@ -35,9 +58,22 @@ fn main() {
         "server" // Default to server
     };
 
+    // Server-side bots fill the arena up to this many players
+    let bot_players = args
+        .iter()
+        .position(|arg| arg == "--bots")
+        .and_then(|index| args.get(index + 1))
+        .and_then(|arg| arg.parse().ok());
+
     match mode {
-        "server" => run_server(),
+        "server" => run_server(bot_players),
         "client" => {
+            let spectate = args.contains(&"--spectate".to_string());
+            let record = args
+                .iter()
+                .position(|arg| arg == "--record")
+                .and_then(|index| args.get(index + 1))
+                .cloned();
             let server_ip = if args.contains(&"--ask".to_string()) {
                 // Prompt for IP address with hidden input
                 use std::io::{self, Write};
@@ -58,53 +94,107 @@ fn main() {
                         "127.0.0.1".to_string()
                     }
                 }
-            } else if args.len() > 2 && args[2] != "--ask" {
+            } else if args.len() > 2 && !args[2].starts_with("--") {
                 args[2].clone()
             } else {
                 "127.0.0.1".to_string() // Default to localhost
             };
-            run_client(server_ip)
+            run_client(server_ip, spectate, record)
+        }
+        "host" => run_host(args.contains(&"--spectate".to_string()), bot_players),
+        "playback" if args.len() > 2 => run_playback(&args[2]),
+        "bots" => {
+            let count = match args.get(2).filter(|arg| !arg.starts_with("--")) {
+                Some(arg) => match arg.parse() {
+                    Ok(count) => count,
+                    Err(_) => {
+                        eprintln!("Invalid bot count '{}'", arg);
+                        print_usage(&args[0]);
+                        return;
+                    }
+                },
+                None => 8,
+            };
+            let server_ip = args
+                .get(3)
+                .filter(|arg| !arg.starts_with("--"))
+                .cloned()
+                .unwrap_or_else(|| "127.0.0.1".to_string());
+            let option = |name: &str| {
+                args.iter()
+                    .position(|arg| arg == name)
+                    .and_then(|index| args.get(index + 1))
+            };
+            let duration = option("--duration").and_then(|arg| arg.parse().ok()).unwrap_or(30.0);
+            let script = match option("--script") {
+                Some(name) => match BotScript::parse(name) {
+                    Some(script) => script,
+                    None => {
+                        eprintln!("Unknown bot script '{}', expected random or circle", name);
+                        print_usage(&args[0]);
+                        return;
+                    }
+                },
+                None => BotScript::Random,
+            };
+            run_bots(count, server_ip, duration, script)
         }
-        _ => {
-            eprintln!("Usage: {} [server|client] [server_ip|--ask]", args[0]);
-            eprintln!("  server - Run as server (default)");
-            eprintln!("  client [server_ip] - Run as client (default server_ip: 127.0.0.1)");
-            eprintln!("  client --ask - Prompt for server IP address");
-            eprintln!("\nExamples:");
-            eprintln!("  {} client 192.168.1.100", args[0]);
-            eprintln!("  {} client --ask", args[0]);
+        "bench-ai" => {
+            let enemies = args.get(2).and_then(|arg| arg.parse().ok()).unwrap_or(2000);
+            let duration = args
+                .iter()
+                .position(|arg| arg == "--duration")
+                .and_then(|index| args.get(index + 1))
+                .and_then(|arg| arg.parse().ok())
+                .unwrap_or(20.0);
+            run_ai_bench(enemies, duration)
         }
+        _ => print_usage(&args[0]),
     }
 }
 
-fn run_server() {
+fn print_usage(program: &str) {
+    eprintln!("Usage: {} [server|client|host|playback] [server_ip|--ask] [--spectate] [--record <file>]", program);
+    eprintln!("  server - Run as server (default)");
+    eprintln!("  host - Run a server and play on it from the same window");
+    eprintln!("  server|host --bots <count> - Fill the arena with bots up to this many players (default 4, 0 disables)");
+    eprintln!("  client [server_ip] - Run as client (default server_ip: 127.0.0.1)");
+    eprintln!("  client --ask - Prompt for server IP address");
+    eprintln!("  client --spectate - Join as a spectator instead of a player");
+    eprintln!("  client --record <file> - Record the match to a demo file");
+    eprintln!("  playback <file> - Replay a recorded demo file");
+    eprintln!("  bots [count] [server_ip] [--duration secs] [--script random|circle] - Load test with headless clients");
+    eprintln!("  bench-ai [enemies] [--duration secs] - Measure the server AI tick with many enemies (default 2000)");
+    eprintln!("\nExamples:");
+    eprintln!("  {} client 192.168.1.100", program);
+    eprintln!("  {} client --ask", program);
+    eprintln!("  {} client 192.168.1.100 --spectate", program);
+    eprintln!("  {} playback match.demo", program);
+    eprintln!("  {} bots 10 127.0.0.1 --duration 60", program);
+    eprintln!("  {} bench-ai 5000 --duration 30", program);
+}
+
+fn run_server(bot_players: Option<usize>) {
     println!("Starting headless server on port {}...", PORT);
 
-    App::new()
-        .add_plugins((
-            MinimalPlugins,
-            AssetPlugin::default(),
-            bevy::state::app::StatesPlugin,
-            TransformPlugin,
-            RepliconPlugins,
-            RepliconRenetPlugins,
-            RapierPhysicsPlugin::<NoUserData>::default(),
-        ))
-        .replicate::<Player>()
-        .replicate::<PlayerPosition>()
-        .replicate::<PlayerRotation>()
-        .replicate::<Health>()
-        .replicate::<Enemy>()
-        .replicate::<EnemyPosition>()
-        .add_client_message::<RotationInput>(Channel::Unordered)
-        .add_client_message::<MovementInput>(Channel::Unordered)
-        .add_client_message::<ShootEvent>(Channel::Unordered)
-        .add_systems(Startup, (setup_server, init_server_state, setup_server_world))
-        .add_systems(Update, (server_connection_system, spawn_players_system, spawn_enemies_system, enemy_fsm_system, enemy_flocking_system, enemy_movement_system, handle_rotation_input, handle_movement_input, handle_shoot_events, respawn_players_system, sync_transform_to_position, sync_transform_to_enemy_position))
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        asset_plugin(),
+        bevy::state::app::StatesPlugin,
+        TransformPlugin,
+        RepliconPlugins,
+        RepliconRenetPlugins,
+        RapierPhysicsPlugin::<NoUserData>::default(),
+    ));
+    register_protocol(&mut app);
+    add_level_systems(&mut app);
+    add_server_systems(&mut app, bot_players);
+    app.add_systems(Startup, (setup_server, init_server_state, setup_server_world))
         .run();
 }
 
-fn run_client(server_ip: String) {
+fn run_client(server_ip: String, spectate: bool, record: Option<String>) {
     let display_ip = if server_ip == "127.0.0.1" || server_ip == "localhost" {
         server_ip.clone()
     } else {
@@ -112,49 +202,407 @@ fn run_client(server_ip: String) {
     };
     println!("Starting client, connecting to {}:{}...", display_ip, PORT);
 
-    App::new()
-        .add_plugins((
-            DefaultPlugins.set(WindowPlugin {
-                primary_window: Some(Window {
-                    title: "FPS Multiplayer Client".to_string(),
-                    resolution: (1280, 720).into(),
-                    ..default()
-                }),
-                ..default()
-            }),
-            RepliconPlugins,
-            RepliconRenetPlugins,
-            RapierPhysicsPlugin::<NoUserData>::default(),
-            RapierDebugRenderPlugin::default(), // Physics debug visualization
-            CursorPlugin,
-            TracerPlugin,
-        ))
-        .replicate::<Player>()
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins.set(window_plugin("FPS Multiplayer Client")),
+        RepliconPlugins,
+        RepliconRenetPlugins,
+        RapierPhysicsPlugin::<NoUserData>::default(),
+        RapierDebugRenderPlugin::default(), // Physics debug visualization
+        CursorPlugin,
+        TracerPlugin,
+    ));
+    register_protocol(&mut app);
+    add_level_systems(&mut app);
+    add_client_systems(&mut app);
+    app.insert_resource(network::ServerIpAddress(server_ip))
+        .insert_resource(network::JoinAsSpectator(spectate))
+        .add_systems(Startup, (setup_client, setup_world, setup_crosshair))
+        // Only remote clients need to copy replicated positions into Transform
+        .add_systems(Update, (client_connection_system, sync_enemy_position, sync_player_position));
+
+    if let Some(path) = record {
+        match DemoRecorder::create(Path::new(&path)) {
+            Ok(recorder) => {
+                println!("Recording demo to {}", path);
+                app.insert_resource(recorder).add_systems(
+                    PreUpdate,
+                    record_demo
+                        .after(ClientSystems::ReceivePackets)
+                        .before(ClientSystems::Receive),
+                );
+            }
+            Err(err) => eprintln!("Failed to create demo file {}: {}", path, err),
+        }
+    }
+
+    app.run();
+}
+
+// Replay a recorded demo through the normal client rendering systems, without a server
+fn run_playback(path: &str) {
+    let frames = match read_demo(Path::new(path)) {
+        Ok(frames) => frames,
+        Err(err) => {
+            eprintln!("Failed to read demo file {}: {}", path, err);
+            return;
+        }
+    };
+
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins.set(window_plugin("FPS Multiplayer Demo Playback")),
+        RepliconPlugins,
+        CursorPlugin,
+    ));
+    register_protocol(&mut app);
+    app.insert_resource(DemoPlayback::new(frames))
+        // Nobody is local in a demo, every player is rendered as a remote player
+        .insert_resource(network::LocalClientId(u64::MAX))
+        .add_systems(Startup, (setup_world, setup_playback_hud, setup_wave_hud, setup_boss_bar, setup_playback_camera, start_demo_playback))
+        .add_systems(
+            PreUpdate,
+            feed_demo_frames
+                .after(ClientSystems::ReceivePackets)
+                .before(ClientSystems::Receive),
+        )
+        .add_systems(PostUpdate, discard_demo_sent_messages)
+        .add_systems(
+            Update,
+            (
+                demo_playback_controls,
+                update_playback_hud,
+                update_wave_hud,
+                update_boss_bar,
+                render_replicated_players,
+                (refresh_enemy_appearance, render_enemies_system).chain(),
+                render_dead_enemies,
+                render_enemy_states,
+                render_projectiles_system,
+                sync_projectile_position,
+                (render_area_attack_warnings, animate_area_attack_warnings).chain(),
+                sync_enemy_position,
+                sync_player_position,
+                sync_remote_player_rotation,
+                update_spectator_camera,
+            ),
+        )
+        .run();
+}
+
+// Open many real netcode connections from one process, each bot is its own headless App
+fn run_bots(count: usize, server_ip: String, duration_secs: f32, script: BotScript) {
+    let bot_tick = Duration::from_secs_f64(1.0 / SERVER_TICK_RATE);
+
+    println!("Starting {} load test bots against {}:{} for {:.0}s...", count, server_ip, PORT, duration_secs);
+
+    let base_id = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap()
+        .as_millis() as u64
+        * 1000;
+
+    let mut bots: Vec<App> = (0..count)
+        .map(|index| {
+            let mut app = App::new();
+            app.add_plugins((
+                MinimalPlugins,
+                bevy::state::app::StatesPlugin,
+                RepliconPlugins,
+                RepliconRenetPlugins,
+            ));
+            register_protocol(&mut app);
+            app.insert_resource(network::ServerIpAddress(server_ip.clone()))
+                .insert_resource(network::JoinAsSpectator(false))
+                .insert_resource(network::LocalClientId(base_id + index as u64))
+                .insert_resource(LoadTestBot::new(script))
+                .init_resource::<LoadTestMetrics>()
+                .add_systems(Startup, setup_client)
+                .add_systems(Update, (collect_load_test_metrics, drive_load_test_bot).chain());
+            // Driven by the loop below instead of App::run
+            app.finish();
+            app.cleanup();
+            app
+        })
+        .collect();
+
+    let duration = Duration::from_secs_f32(duration_secs);
+    let started_at = Instant::now();
+    while started_at.elapsed() < duration {
+        let tick_started_at = Instant::now();
+        for app in bots.iter_mut() {
+            app.update();
+        }
+        if let Some(remaining) = bot_tick.checked_sub(tick_started_at.elapsed()) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    // Disconnect cleanly so the server frees the slots right away
+    for app in bots.iter_mut() {
+        app.world_mut().resource_mut::<RenetClient>().disconnect();
+        app.update();
+    }
+
+    let metrics: Vec<&LoadTestMetrics> = bots
+        .iter()
+        .map(|app| app.world().resource::<LoadTestMetrics>())
+        .collect();
+    print_load_test_report(&metrics, started_at.elapsed());
+}
+
+// Headless enemy AI only, no networking: how many enemies fit in the server tick
+fn run_ai_bench(enemies: usize, duration_secs: f32) {
+    const WARM_UP_TIMEOUT: Duration = Duration::from_secs(10);
+    let server_tick = Duration::from_secs_f64(1.0 / SERVER_TICK_RATE);
+
+    println!("Starting AI benchmark with {} enemies for {:.0}s...", enemies, duration_secs);
+
+    let mut app = App::new();
+    app.add_plugins((
+        MinimalPlugins,
+        AssetPlugin::default(),
+        TransformPlugin,
+        RapierPhysicsPlugin::<NoUserData>::default(),
+    ));
+    add_level_systems(&mut app);
+    add_enemy_ai_systems(&mut app);
+    app.insert_resource(AiBench::new(enemies))
+        .add_systems(Startup, setup_server_world)
+        .add_systems(Update, (spawn_ai_bench, move_ai_bench_players).before(rebuild_spatial_index));
+    // Driven by the loop below instead of App::run
+    app.finish();
+    app.cleanup();
+
+    // Wait for the level, navmesh and archetypes before spawning and measuring
+    let warm_up_started_at = Instant::now();
+    while !app.world().resource::<AiBench>().spawned {
+        if warm_up_started_at.elapsed() > WARM_UP_TIMEOUT {
+            eprintln!("AI benchmark scene did not load within {:.0}s", WARM_UP_TIMEOUT.as_secs_f32());
+            return;
+        }
+        app.update();
+        std::thread::sleep(server_tick);
+    }
+
+    // LOD metrics only cover the measured ticks
+    app.world_mut().resource_mut::<AiLodMetrics>().reset();
+    let duration = Duration::from_secs_f32(duration_secs);
+    let mut samples = Vec::new();
+    let started_at = Instant::now();
+    while started_at.elapsed() < duration {
+        let tick_started_at = Instant::now();
+        app.update();
+        let tick_time = tick_started_at.elapsed();
+        samples.push(tick_time);
+        if let Some(remaining) = server_tick.checked_sub(tick_time) {
+            std::thread::sleep(remaining);
+        }
+    }
+
+    print_ai_bench_report(enemies, &samples, server_tick, app.world().resource::<AiLodMetrics>());
+}
+
+fn setup_playback_camera(mut commands: Commands) {
+    spawn_spectator_camera(&mut commands);
+}
+
+// Listen server: authoritative server and a local client in one app.
+// The host's input goes through the same messages as remote clients
+// without touching the network, remote clients still connect over UDP.
+fn run_host(spectate: bool, bot_players: Option<usize>) {
+    println!("Starting host on port {}...", PORT);
+
+    let mut app = App::new();
+    app.add_plugins((
+        DefaultPlugins
+            .set(window_plugin("FPS Multiplayer Host"))
+            .set(asset_plugin()),
+        RepliconPlugins,
+        RepliconRenetPlugins,
+        RapierPhysicsPlugin::<NoUserData>::default(),
+        RapierDebugRenderPlugin::default(), // Physics debug visualization
+        CursorPlugin,
+        TracerPlugin,
+    ));
+    register_protocol(&mut app);
+    add_level_systems(&mut app);
+    add_server_systems(&mut app, bot_players);
+    add_client_systems(&mut app);
+    app.insert_resource(network::LocalClientId(HOST_CLIENT_ID))
+        .insert_resource(network::JoinAsSpectator(spectate))
+        // setup_world already spawns the ground collider, so skip setup_server_world
+        .add_systems(Startup, (setup_server, init_server_state, setup_world, setup_crosshair))
+        .add_systems(PostStartup, spawn_host_player)
+        .run();
+}
+
+fn window_plugin(title: &str) -> WindowPlugin {
+    WindowPlugin {
+        primary_window: Some(Window {
+            title: title.to_string(),
+            resolution: (1280, 720).into(),
+            ..default()
+        }),
+        ..default()
+    }
+}
+
+// Servers watch the assets folder so edited enemy archetypes apply without a restart
+fn asset_plugin() -> AssetPlugin {
+    AssetPlugin {
+        watch_for_changes_override: Some(true),
+        ..default()
+    }
+}
+
+// Replicated components and messages, must be registered in the same order on every side
+fn register_protocol(app: &mut App) {
+    app.replicate::<Player>()
         .replicate::<PlayerPosition>()
         .replicate::<PlayerRotation>()
         .replicate::<Health>()
+        .replicate::<PlayerStats>()
         .replicate::<Enemy>()
         .replicate::<EnemyPosition>()
+        .replicate::<EnemyAppearance>()
+        .replicate::<EnemyState>()
+        .replicate::<Projectile>()
+        .replicate::<Boss>()
+        .replicate::<AreaAttackWarning>()
+        .replicate::<Spectator>()
+        .replicate::<MatchClock>()
+        .replicate::<WaveInfo>()
         .add_client_message::<RotationInput>(Channel::Unordered)
         .add_client_message::<MovementInput>(Channel::Unordered)
         .add_client_message::<ShootEvent>(Channel::Unordered)
-        .init_resource::<PlayerInput>()
-        .insert_resource(network::ServerIpAddress(server_ip))
-        .add_systems(Startup, (setup_client, setup_world, setup_crosshair))
+        .add_client_message::<SpectateRequest>(Channel::Ordered)
+        .add_client_message::<ClockSyncRequest>(Channel::Unordered)
+        .add_server_message::<ClockSyncResponse>(Channel::Unordered);
+}
+
+// Level colliders exist on every side with physics so movement and shots agree
+fn add_level_systems(app: &mut App) {
+    app.init_asset::<Level>()
+        .init_asset_loader::<LevelLoader>()
+        .add_systems(Startup, load_level)
+        .add_systems(Update, spawn_level_geometry);
+}
+
+fn add_server_systems(app: &mut App, bot_players: Option<usize>) {
+    let mut bot_settings = BotSettings::default();
+    if let Some(target_players) = bot_players {
+        bot_settings.target_players = target_players;
+    }
+
+    app.insert_resource(bot_settings)
+        .init_resource::<WaveConfig>()
+        .init_resource::<PacingConfig>()
+        .init_resource::<Pacing>()
+        .add_message::<EnemyHit>()
+        .add_systems(Startup, (setup_match_clock, setup_wave_director))
+        .add_systems(Update, (respond_clock_sync, update_match_clock))
+        .add_systems(Update, log_enemy_state_changes.after(track_enemy_state_changes))
+        // The pacing director reads what happened this tick before the wave director spawns
+        .add_systems(Update, (track_pacing_signals, pacing_director_system).chain().after(handle_shoot_events).before(wave_director_system))
+        // Bot inputs are read by the input handlers in the same tick, like a client's
+        .add_systems(
+            Update,
+            (balance_bots, drive_bots)
+                .chain()
+                .before(handle_rotation_input)
+                .before(handle_movement_input)
+                .before(handle_shoot_events),
+        )
+        .add_systems(Update, (server_connection_system, spawn_players_system, handle_spectate_requests, wave_director_system.after(reload_enemy_archetypes), handle_rotation_input, handle_movement_input, handle_shoot_events, respawn_players_system, sync_transform_to_position));
+    add_enemy_ai_systems(app);
+}
+
+// Everything enemies need to think and move, shared by the server and the AI benchmark
+fn add_enemy_ai_systems(app: &mut App) {
+    // BT_DEBUG=1 prints the active behavior tree node of every enemy once a second
+    if std::env::var_os("BT_DEBUG").is_some() {
+        app.insert_resource(BehaviorTreeDebug(Timer::from_seconds(1.0, TimerMode::Repeating)));
+    }
+    // AI_METRICS=1 prints how many enemies thought per LOD tier every 5 seconds
+    if std::env::var_os("AI_METRICS").is_some() {
+        app.insert_resource(AiLodReport(Timer::from_seconds(5.0, TimerMode::Repeating)));
+    }
+
+    app.init_resource::<NavMeshConfig>()
+        .init_resource::<SpatialIndex>()
+        .init_resource::<SquadConfig>()
+        .init_resource::<CoverConfig>()
+        .init_resource::<AiLodConfig>()
+        .init_resource::<AiLodMetrics>()
+        .add_message::<Gunshot>()
+        .add_message::<EnemyStateChanged>()
+        .init_asset::<EnemyArchetype>()
+        .init_asset_loader::<EnemyArchetypeLoader>()
+        .add_systems(Startup, load_enemy_archetypes)
+        .add_systems(Update, (reload_enemy_archetypes, despawn_dead_enemies))
+        .add_systems(Update, dump_behavior_trees.run_if(resource_exists::<BehaviorTreeDebug>))
+        .add_systems(Update, report_enemy_ai_lod.after(measure_enemy_ai).run_if(resource_exists::<AiLodReport>))
+        .add_systems(
+            Update,
+            build_navmesh.run_if(resource_exists::<LevelReady>.and(not(resource_exists::<NavMesh>))),
+        )
+        .add_systems(
+            Update,
+            build_cover_points.run_if(resource_exists::<NavMesh>.and(not(resource_exists::<CoverPoints>))),
+        )
+        // The spatial index is rebuilt once per tick, before anything queries it,
+        // then the LOD scheduler picks which enemies think this tick
+        .add_systems(
+            Update,
+            (
+                rebuild_spatial_index,
+                schedule_enemy_ai,
+                enemy_perception_system,
+                enemy_behavior_tree_system,
+                track_enemy_state_changes,
+                boss_phase_system,
+                boss_ability_system,
+                resolve_area_attacks,
+                assign_squad_slots,
+                enemy_cover_system,
+                enemy_attack_system,
+                simulate_projectiles,
+                enemy_flocking_system,
+                enemy_pathfinding_system,
+                enemy_movement_system,
+                flying_enemy_movement_system,
+                sync_transform_to_enemy_position,
+                measure_enemy_ai,
+            )
+                .chain(),
+        );
+}
+
+fn add_client_systems(app: &mut App) {
+    app.init_resource::<PlayerInput>()
+        .init_resource::<ServerClock>()
+        .add_systems(Startup, (setup_match_timer, setup_wave_hud, setup_boss_bar))
+        .add_systems(Update, (send_clock_sync, receive_clock_sync, update_match_timer).chain())
+        .add_systems(Update, (update_wave_hud, update_boss_bar))
         .add_systems(
             Update,
             (
-                client_connection_system,
                 render_replicated_players,
-                render_enemies_system,
-                sync_enemy_position,
+                (refresh_enemy_appearance, render_enemies_system).chain(),
+                render_dead_enemies,
+                render_enemy_states,
+                render_projectiles_system,
+                sync_projectile_position,
+                (render_area_attack_warnings, animate_area_attack_warnings).chain(),
                 sync_remote_player_rotation,
-                sync_player_position,
                 update_camera_controller,
                 update_movement_input,
                 apply_local_movement,
                 handle_shooting,
+                send_spectate_toggle,
+                manage_spectator_camera,
+                update_spectator_camera,
             ),
-        )
-        .run();
+        );
 }
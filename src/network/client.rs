@@ -10,6 +10,7 @@ use std::{
     time::SystemTime,
 };
 
+use super::protocol::ConnectRequest;
 use super::protocol::PORT;
 use super::protocol::PROTOCOL_ID;
 
@@ -21,10 +22,16 @@ pub struct LocalClientId(pub u64);
 #[derive(Resource)]
 pub struct ServerIpAddress(pub String);
 
+// Resource to request joining as a spectator instead of a player
+#[derive(Resource, Default)]
+pub struct JoinAsSpectator(pub bool);
+
 pub fn setup_client(
     mut commands: Commands,
     channels: Res<RepliconChannels>,
     server_ip: Res<ServerIpAddress>,
+    join_as_spectator: Res<JoinAsSpectator>,
+    preset_client_id: Option<Res<LocalClientId>>,
 ) {
     let server_addr: SocketAddr = format!("{}:{}", server_ip.0, PORT)
         .parse()
@@ -36,12 +43,16 @@ pub fn setup_client(
         .duration_since(SystemTime::UNIX_EPOCH)
         .unwrap();
 
-    let client_id = current_time.as_millis() as u64;
+    // Connection timestamp unless an id was chosen up front (load test bots share one timestamp)
+    let client_id = preset_client_id.map_or(current_time.as_millis() as u64, |id| id.0);
+    let connect_request = ConnectRequest {
+        spectator: join_as_spectator.0,
+    };
     let authentication = ClientAuthentication::Unsecure {
         client_id,
         protocol_id: PROTOCOL_ID,
         server_addr,
-        user_data: None,
+        user_data: Some(connect_request.to_user_data()),
     };
 
     let transport = NetcodeClientTransport::new(current_time, authentication, socket)
@@ -0,0 +1,134 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+
+use super::protocol::{ClockSyncRequest, ClockSyncResponse, MatchClock, MATCH_DURATION_SECS};
+
+// Send sync requests quickly until the estimate settles, then slow down
+const FAST_SYNC_INTERVAL: f32 = 0.2;
+const SYNC_INTERVAL: f32 = 2.0;
+const FAST_SYNC_SAMPLES: u32 = 10;
+// Weight of each new sample in the smoothed offset and RTT
+const SMOOTHING: f64 = 0.2;
+
+// Client estimate of the server clock
+#[derive(Resource)]
+pub struct ServerClock {
+    offset: f64, // server_time - client_time
+    rtt: f64,
+    samples: u32,
+    timer: Timer,
+}
+
+impl Default for ServerClock {
+    fn default() -> Self {
+        Self {
+            offset: 0.0,
+            rtt: 0.0,
+            samples: 0,
+            timer: Timer::from_seconds(FAST_SYNC_INTERVAL, TimerMode::Repeating),
+        }
+    }
+}
+
+impl ServerClock {
+    pub fn is_synced(&self) -> bool {
+        self.samples > 0
+    }
+
+    // Round trip time in seconds
+    pub fn rtt(&self) -> f64 {
+        self.rtt
+    }
+
+    // Estimated server time for the given local time
+    pub fn server_time(&self, client_time: f64) -> f64 {
+        client_time + self.offset
+    }
+
+    fn add_sample(&mut self, offset: f64, rtt: f64) {
+        if self.samples == 0 {
+            self.offset = offset;
+            self.rtt = rtt;
+        } else {
+            self.offset += (offset - self.offset) * SMOOTHING;
+            self.rtt += (rtt - self.rtt) * SMOOTHING;
+        }
+        self.samples += 1;
+    }
+}
+
+// Server-side: answer clock sync requests with the current server time
+pub fn respond_clock_sync(
+    mut requests: MessageReader<FromClient<ClockSyncRequest>>,
+    mut responses: MessageWriter<ToClients<ClockSyncResponse>>,
+    time: Res<Time<Real>>,
+) {
+    for request in requests.read() {
+        responses.write(ToClients {
+            mode: SendMode::Direct(request.client_id),
+            message: ClockSyncResponse {
+                client_time: request.message.client_time,
+                server_time: time.elapsed_secs_f64(),
+            },
+        });
+    }
+}
+
+// Client-side: periodically ask the server for its time
+pub fn send_clock_sync(
+    mut clock: ResMut<ServerClock>,
+    mut requests: MessageWriter<ClockSyncRequest>,
+    time: Res<Time<Real>>,
+) {
+    clock.timer.tick(time.delta());
+    if !clock.timer.just_finished() {
+        return;
+    }
+
+    if clock.samples >= FAST_SYNC_SAMPLES {
+        clock.timer.set_duration(std::time::Duration::from_secs_f32(SYNC_INTERVAL));
+    }
+
+    requests.write(ClockSyncRequest {
+        client_time: time.elapsed_secs_f64(),
+    });
+}
+
+// Client-side: estimate offset and RTT from the server's answers
+pub fn receive_clock_sync(
+    mut responses: MessageReader<ClockSyncResponse>,
+    mut clock: ResMut<ServerClock>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for response in responses.read() {
+        let rtt = (now - response.client_time).max(0.0);
+        // Assume the response took half the round trip to arrive
+        let offset = response.server_time + rtt / 2.0 - now;
+        clock.add_sample(offset, rtt);
+    }
+}
+
+// Server-side: spawn the replicated match clock
+pub fn setup_match_clock(mut commands: Commands, time: Res<Time<Real>>) {
+    commands.spawn((
+        MatchClock {
+            started_at: time.elapsed_secs_f64(),
+            duration: MATCH_DURATION_SECS,
+        },
+        Replicated,
+    ));
+}
+
+// Server-side: restart the match clock when the match is over
+pub fn update_match_clock(mut clocks: Query<&mut MatchClock>, time: Res<Time<Real>>) {
+    let now = time.elapsed_secs_f64();
+
+    for mut clock in clocks.iter_mut() {
+        if now >= clock.started_at + clock.duration as f64 {
+            clock.started_at = now;
+            println!("[SERVER] Match over, starting a new {:.0}s match", clock.duration);
+        }
+    }
+}
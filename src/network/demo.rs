@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy_replicon::prelude::*;
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Read, Write},
+    path::Path,
+};
+
+// Demo file layout: MAGIC, then frames of (time: f64, channel: u8, len: u32, bytes), little endian
+const MAGIC: &[u8; 8] = b"BMDEMO01";
+const SEEK_STEP_SECS: f64 = 5.0;
+const MIN_SPEED: f64 = 0.25;
+const MAX_SPEED: f64 = 8.0;
+
+// One message received from the server on a replicon channel
+pub struct DemoFrame {
+    pub time: f64, // Seconds since the first recorded message
+    pub channel: u8,
+    pub bytes: Vec<u8>,
+}
+
+// Client-side: writes every incoming server message to a demo file
+#[derive(Resource)]
+pub struct DemoRecorder {
+    writer: BufWriter<File>,
+    started_at: Option<f64>,
+}
+
+impl DemoRecorder {
+    pub fn create(path: &Path) -> io::Result<Self> {
+        let mut writer = BufWriter::new(File::create(path)?);
+        writer.write_all(MAGIC)?;
+        Ok(Self {
+            writer,
+            started_at: None,
+        })
+    }
+
+    fn write_frame(&mut self, now: f64, channel: u8, bytes: &[u8]) -> io::Result<()> {
+        let started_at = *self.started_at.get_or_insert(now);
+        self.writer.write_all(&(now - started_at).to_le_bytes())?;
+        self.writer.write_all(&[channel])?;
+        self.writer.write_all(&(bytes.len() as u32).to_le_bytes())?;
+        self.writer.write_all(bytes)
+    }
+}
+
+pub fn read_demo(path: &Path) -> io::Result<Vec<DemoFrame>> {
+    let mut reader = BufReader::new(File::open(path)?);
+
+    let mut magic = [0; MAGIC.len()];
+    reader.read_exact(&mut magic)?;
+    if &magic != MAGIC {
+        return Err(io::Error::new(io::ErrorKind::InvalidData, "not a demo file"));
+    }
+
+    let mut frames = Vec::new();
+    loop {
+        let mut time = [0; 8];
+        match reader.read_exact(&mut time) {
+            Ok(()) => {}
+            Err(err) if err.kind() == io::ErrorKind::UnexpectedEof => break,
+            Err(err) => return Err(err),
+        }
+
+        let mut channel = [0; 1];
+        reader.read_exact(&mut channel)?;
+        let mut len = [0; 4];
+        reader.read_exact(&mut len)?;
+        let mut bytes = vec![0; u32::from_le_bytes(len) as usize];
+        reader.read_exact(&mut bytes)?;
+
+        frames.push(DemoFrame {
+            time: f64::from_le_bytes(time),
+            channel: channel[0],
+            bytes,
+        });
+    }
+
+    Ok(frames)
+}
+
+// Client-side: copy messages received by the backend into the demo before replicon consumes them.
+// Runs between ClientSystems::ReceivePackets and ClientSystems::Receive.
+pub fn record_demo(
+    mut recorder: ResMut<DemoRecorder>,
+    mut messages: ResMut<ClientMessages>,
+    channels: Res<RepliconChannels>,
+    time: Res<Time<Real>>,
+) {
+    let now = time.elapsed_secs_f64();
+
+    for channel_id in 0..channels.server_channels().len() {
+        let received: Vec<Vec<u8>> = messages
+            .receive(channel_id)
+            .map(|message| message.to_vec())
+            .collect();
+
+        for message in received {
+            if let Err(err) = recorder.write_frame(now, channel_id as u8, &message) {
+                error!("Failed to write demo frame: {}", err);
+            }
+            messages.insert_received(channel_id, message);
+        }
+    }
+
+    if let Err(err) = recorder.writer.flush() {
+        error!("Failed to flush demo file: {}", err);
+    }
+}
+
+// Playback state for a loaded demo
+#[derive(Resource)]
+pub struct DemoPlayback {
+    pub frames: Vec<DemoFrame>,
+    pub time: f64,
+    pub speed: f64,
+    pub paused: bool,
+    cursor: usize,
+    // Set when seeking backwards, replication restarts from the first frame
+    restart: bool,
+}
+
+impl DemoPlayback {
+    pub fn new(frames: Vec<DemoFrame>) -> Self {
+        Self {
+            frames,
+            time: 0.0,
+            speed: 1.0,
+            paused: false,
+            cursor: 0,
+            restart: false,
+        }
+    }
+
+    pub fn duration(&self) -> f64 {
+        self.frames.last().map_or(0.0, |frame| frame.time)
+    }
+
+    pub fn seek(&mut self, time: f64) {
+        let time = time.clamp(0.0, self.duration());
+        if time < self.time {
+            self.restart = true;
+        }
+        self.time = time;
+    }
+}
+
+// Playback-side: there is no backend, so mark the client as connected for replicon
+pub fn start_demo_playback(mut client_state: ResMut<NextState<ClientState>>, playback: Res<DemoPlayback>) {
+    client_state.set(ClientState::Connected);
+    println!(
+        "Playing demo: {} messages, {:.1}s - P: pause, Left/Right: seek, Up/Down: speed",
+        playback.frames.len(),
+        playback.duration()
+    );
+}
+
+// Playback-side: pause, seek and speed controls
+pub fn demo_playback_controls(keys: Res<ButtonInput<KeyCode>>, mut playback: ResMut<DemoPlayback>) {
+    if keys.just_pressed(KeyCode::KeyP) {
+        playback.paused = !playback.paused;
+    }
+    if keys.just_pressed(KeyCode::ArrowRight) {
+        let time = playback.time + SEEK_STEP_SECS;
+        playback.seek(time);
+    }
+    if keys.just_pressed(KeyCode::ArrowLeft) {
+        let time = playback.time - SEEK_STEP_SECS;
+        playback.seek(time);
+    }
+    if keys.just_pressed(KeyCode::ArrowUp) {
+        playback.speed = (playback.speed * 2.0).min(MAX_SPEED);
+    }
+    if keys.just_pressed(KeyCode::ArrowDown) {
+        playback.speed = (playback.speed / 2.0).max(MIN_SPEED);
+    }
+}
+
+// Playback-side: feed recorded messages to replicon as if the backend received them.
+// Runs between ClientSystems::ReceivePackets and ClientSystems::Receive.
+pub fn feed_demo_frames(
+    mut commands: Commands,
+    mut playback: ResMut<DemoPlayback>,
+    mut messages: ResMut<ClientMessages>,
+    client_state: Res<State<ClientState>>,
+    mut next_client_state: ResMut<NextState<ClientState>>,
+    // Replicon marks every entity it spawned from the server, whatever it carries
+    replicated: Query<Entity, With<Replicated>>,
+    time: Res<Time<Real>>,
+) {
+    if playback.restart {
+        // Clear the replicated world and reconnect so replicon resets its entity map and ticks
+        if *client_state.get() == ClientState::Connected {
+            for entity in replicated.iter() {
+                commands.entity(entity).despawn();
+            }
+            next_client_state.set(ClientState::Disconnected);
+            return;
+        }
+
+        next_client_state.set(ClientState::Connected);
+        playback.restart = false;
+        playback.cursor = 0;
+        return;
+    }
+
+    if *client_state.get() != ClientState::Connected {
+        return;
+    }
+
+    if !playback.paused {
+        let duration = playback.duration();
+        playback.time = (playback.time + time.delta_secs_f64() * playback.speed).min(duration);
+    }
+
+    let playback = &mut *playback;
+    while let Some(frame) = playback.frames.get(playback.cursor) {
+        if frame.time > playback.time {
+            break;
+        }
+        messages.insert_received(frame.channel as usize, frame.bytes.clone());
+        playback.cursor += 1;
+    }
+}
+
+// Playback-side: nobody is listening, drop acks and messages the client tries to send
+pub fn discard_demo_sent_messages(mut messages: ResMut<ClientMessages>) {
+    messages.drain_sent().for_each(drop);
+}
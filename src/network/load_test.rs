@@ -0,0 +1,230 @@
+use bevy::prelude::*;
+use bevy_replicon_renet::renet::RenetClient;
+use rand::Rng;
+use std::time::{Duration, Instant};
+
+use super::client::LocalClientId;
+use super::protocol::{Enemy, MovementInput, Player, PlayerPosition, RotationInput, ShootEvent};
+
+const EYE_HEIGHT: f32 = 1.6;
+const SHOOT_INTERVAL_SECS: f32 = 1.0;
+
+// How a load test bot generates its input
+#[derive(Clone, Copy, PartialEq)]
+pub enum BotScript {
+    // Pick a new random direction and aim every few seconds
+    Random,
+    // Walk in a circle while turning at a constant rate
+    Circle,
+}
+
+impl BotScript {
+    pub fn parse(name: &str) -> Option<Self> {
+        match name {
+            "random" => Some(Self::Random),
+            "circle" => Some(Self::Circle),
+            _ => None,
+        }
+    }
+}
+
+// State of one load test bot, lives in that bot's own App
+#[derive(Resource)]
+pub struct LoadTestBot {
+    pub script: BotScript,
+    movement: Vec2,
+    yaw: f32,
+    pitch: f32,
+    next_decision: f32,
+    next_shot: f32,
+}
+
+impl LoadTestBot {
+    pub fn new(script: BotScript) -> Self {
+        Self {
+            script,
+            movement: Vec2::ZERO,
+            yaw: 0.0,
+            pitch: 0.0,
+            next_decision: 0.0,
+            next_shot: SHOOT_INTERVAL_SECS,
+        }
+    }
+}
+
+// Server response metrics collected by one bot
+#[derive(Resource)]
+pub struct LoadTestMetrics {
+    pub started_at: Instant,
+    pub connect_time: Option<Duration>,
+    pub rtt_sum: f64,
+    pub rtt_max: f64,
+    pub rtt_samples: u32,
+    pub shots_fired: u32,
+    pub players_seen: usize,
+    pub enemies_seen: usize,
+    pub max_players_seen: usize,
+    pub max_enemies_seen: usize,
+}
+
+impl Default for LoadTestMetrics {
+    fn default() -> Self {
+        Self {
+            started_at: Instant::now(),
+            connect_time: None,
+            rtt_sum: 0.0,
+            rtt_max: 0.0,
+            rtt_samples: 0,
+            shots_fired: 0,
+            players_seen: 0,
+            enemies_seen: 0,
+            max_players_seen: 0,
+            max_enemies_seen: 0,
+        }
+    }
+}
+
+impl LoadTestMetrics {
+    pub fn average_rtt(&self) -> Option<f64> {
+        (self.rtt_samples > 0).then(|| self.rtt_sum / self.rtt_samples as f64)
+    }
+}
+
+// Send scripted or random movement, rotation and shots like a real client would
+pub fn drive_load_test_bot(
+    time: Res<Time>,
+    client: Res<RenetClient>,
+    local_client_id: Res<LocalClientId>,
+    mut bot: ResMut<LoadTestBot>,
+    mut metrics: ResMut<LoadTestMetrics>,
+    players: Query<(&Player, &PlayerPosition)>,
+    mut movement_writer: MessageWriter<MovementInput>,
+    mut rotation_writer: MessageWriter<RotationInput>,
+    mut shoot_writer: MessageWriter<ShootEvent>,
+) {
+    if !client.is_connected() {
+        return;
+    }
+
+    let delta = time.delta_secs();
+    let mut rng = rand::thread_rng();
+
+    match bot.script {
+        BotScript::Random => {
+            bot.next_decision -= delta;
+            if bot.next_decision <= 0.0 {
+                bot.next_decision = rng.gen_range(1.0..3.0);
+                bot.movement = Vec2::new(rng.gen_range(-1.0..=1.0_f32).round(), rng.gen_range(-1.0..=1.0_f32).round());
+                bot.yaw = rng.gen_range(-180.0..180.0);
+                bot.pitch = rng.gen_range(-20.0..20.0);
+            }
+        }
+        BotScript::Circle => {
+            bot.movement = Vec2::new(1.0, 0.0);
+            bot.yaw = (bot.yaw + 45.0 * delta) % 360.0;
+            bot.pitch = 0.0;
+        }
+    }
+
+    rotation_writer.write(RotationInput {
+        yaw: bot.yaw,
+        pitch: bot.pitch,
+    });
+    movement_writer.write(MovementInput {
+        forward: bot.movement.x,
+        right: bot.movement.y,
+    });
+
+    bot.next_shot -= delta;
+    if bot.next_shot > 0.0 {
+        return;
+    }
+    bot.next_shot = SHOOT_INTERVAL_SECS;
+
+    // Shoot from our replicated position along the current aim
+    let Some((_, position)) = players.iter().find(|(player, _)| player.id == local_client_id.0) else {
+        return;
+    };
+
+    let rotation = Quat::from_axis_angle(Vec3::Y, bot.yaw.to_radians())
+        * Quat::from_axis_angle(Vec3::X, bot.pitch.to_radians());
+    shoot_writer.write(ShootEvent {
+        origin: Vec3::new(position.x, position.y + EYE_HEIGHT, position.z),
+        direction: rotation * Vec3::NEG_Z,
+    });
+    metrics.shots_fired += 1;
+}
+
+// Sample connection and replication metrics every frame
+pub fn collect_load_test_metrics(
+    client: Res<RenetClient>,
+    mut metrics: ResMut<LoadTestMetrics>,
+    players: Query<&Player>,
+    enemies: Query<&Enemy>,
+) {
+    if !client.is_connected() {
+        return;
+    }
+
+    if metrics.connect_time.is_none() {
+        metrics.connect_time = Some(metrics.started_at.elapsed());
+    }
+
+    let rtt = client.rtt();
+    metrics.rtt_sum += rtt;
+    metrics.rtt_max = metrics.rtt_max.max(rtt);
+    metrics.rtt_samples += 1;
+
+    metrics.players_seen = players.iter().count();
+    metrics.enemies_seen = enemies.iter().count();
+    metrics.max_players_seen = metrics.max_players_seen.max(metrics.players_seen);
+    metrics.max_enemies_seen = metrics.max_enemies_seen.max(metrics.enemies_seen);
+}
+
+// Print a summary over all bots at the end of the run
+pub fn print_load_test_report(metrics: &[&LoadTestMetrics], duration: Duration) {
+    let connected: Vec<&&LoadTestMetrics> = metrics.iter().filter(|m| m.connect_time.is_some()).collect();
+
+    println!("\n=== Load test report ({} bots, {:.0}s) ===", metrics.len(), duration.as_secs_f32());
+    println!("Connected: {}/{}", connected.len(), metrics.len());
+
+    if connected.is_empty() {
+        return;
+    }
+
+    let connect_times: Vec<f64> = connected
+        .iter()
+        .filter_map(|m| m.connect_time)
+        .map(|time| time.as_secs_f64() * 1000.0)
+        .collect();
+    let average = |values: &[f64]| values.iter().sum::<f64>() / values.len() as f64;
+    let max = |values: &[f64]| values.iter().cloned().fold(0.0, f64::max);
+    let min = |values: &[f64]| values.iter().cloned().fold(f64::MAX, f64::min);
+
+    println!(
+        "Connect time: min {:.0} ms, avg {:.0} ms, max {:.0} ms",
+        min(&connect_times),
+        average(&connect_times),
+        max(&connect_times)
+    );
+
+    let rtts: Vec<f64> = connected.iter().filter_map(|m| m.average_rtt()).map(|rtt| rtt * 1000.0).collect();
+    let rtt_max = connected.iter().map(|m| m.rtt_max * 1000.0).fold(0.0, f64::max);
+    if !rtts.is_empty() {
+        println!("RTT: avg {:.1} ms, worst bot avg {:.1} ms, max {:.1} ms", average(&rtts), max(&rtts), rtt_max);
+    }
+
+    let players: Vec<f64> = connected.iter().map(|m| m.players_seen as f64).collect();
+    let enemies: Vec<f64> = connected.iter().map(|m| m.enemies_seen as f64).collect();
+    println!(
+        "Replicated players: avg {:.1} at end, max {}",
+        average(&players),
+        connected.iter().map(|m| m.max_players_seen).max().unwrap_or(0)
+    );
+    println!(
+        "Replicated enemies: avg {:.1} at end, max {}",
+        average(&enemies),
+        connected.iter().map(|m| m.max_enemies_seen).max().unwrap_or(0)
+    );
+    println!("Shots fired: {}", connected.iter().map(|m| m.shots_fired).sum::<u32>());
+}
@@ -1,7 +1,11 @@
 pub mod client;
+pub mod clock;
+pub mod demo;
+pub mod load_test;
 pub mod protocol;
 pub mod server;
 
-pub use client::{client_connection_system, setup_client, ServerIpAddress};
-pub use protocol::{Player, PlayerPosition, PlayerRotation, Health, Enemy, EnemyPosition, PORT};
+pub use client::{client_connection_system, setup_client, ServerIpAddress, JoinAsSpectator, LocalClientId};
+pub use protocol::{Player, PlayerPosition, PlayerRotation, Health, AreaAttackWarning, Boss, Enemy, EnemyAppearance, EnemyPosition, Projectile, Spectator, HOST_CLIENT_ID, PORT};
+pub use clock::{receive_clock_sync, respond_clock_sync, send_clock_sync, setup_match_clock, update_match_clock, ServerClock};
 pub use server::{server_connection_system, setup_server};
@@ -1,9 +1,42 @@
 use bevy::prelude::*;
+use renet_netcode::NETCODE_USER_DATA_BYTES;
 use serde::{Deserialize, Serialize};
 
 // Network constants
 pub const PORT: u16 = 5000;
 pub const PROTOCOL_ID: u64 = 0;
+pub const MAX_CLIENTS: usize = 10;
+// Clients beyond this many players join as spectators
+pub const MAX_PLAYERS: usize = 8;
+// Player id of the local client in host mode (netcode ids are connection timestamps)
+pub const HOST_CLIENT_ID: u64 = 0;
+// Updates per second of the server simulation, headless tools pace their loops with it
+pub const SERVER_TICK_RATE: f64 = 60.0;
+pub const MATCH_DURATION_SECS: f32 = 600.0;
+
+// Connect request sent in the netcode user data when the client connects
+#[derive(Default, Clone, Copy)]
+pub struct ConnectRequest {
+    pub spectator: bool,
+}
+
+impl ConnectRequest {
+    const SPECTATOR_FLAG: u8 = 1;
+
+    pub fn to_user_data(self) -> [u8; NETCODE_USER_DATA_BYTES] {
+        let mut data = [0; NETCODE_USER_DATA_BYTES];
+        if self.spectator {
+            data[0] |= Self::SPECTATOR_FLAG;
+        }
+        data
+    }
+
+    pub fn from_user_data(data: &[u8; NETCODE_USER_DATA_BYTES]) -> Self {
+        Self {
+            spectator: data[0] & Self::SPECTATOR_FLAG != 0,
+        }
+    }
+}
 
 // Client -> Server events
 #[derive(Message, Serialize, Deserialize)]
@@ -24,6 +57,25 @@ pub struct ShootEvent {
     pub direction: Vec3,
 }
 
+// Ask the server to switch between spectating and playing
+#[derive(Message, Serialize, Deserialize)]
+pub struct SpectateRequest {
+    pub spectate: bool,
+}
+
+// Clock sync exchange, times are seconds since each side's app started
+#[derive(Message, Serialize, Deserialize)]
+pub struct ClockSyncRequest {
+    pub client_time: f64,
+}
+
+// Server -> Client events
+#[derive(Message, Serialize, Deserialize)]
+pub struct ClockSyncResponse {
+    pub client_time: f64,
+    pub server_time: f64,
+}
+
 // Replicated components
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Player {
@@ -44,6 +96,19 @@ pub struct PlayerRotation {
     pub pitch: f32, // Vertical rotation (X-axis) in degrees
 }
 
+// Kill/death counters for the scoreboard
+#[derive(Component, Serialize, Deserialize, Default)]
+pub struct PlayerStats {
+    pub kills: u32,
+    pub deaths: u32,
+}
+
+// Connected client that watches the match without a player body
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Spectator {
+    pub id: u64,
+}
+
 #[derive(Component, Serialize, Deserialize)]
 pub struct Health {
     pub current: f32,
@@ -51,14 +116,80 @@ pub struct Health {
 }
 
 // Enemy replicated components
+#[derive(Component, Serialize, Deserialize, Debug, Clone, Copy, PartialEq)]
+pub enum EnemyState {
+    /// Standing still between patrol waypoints
+    Idle,
+    Patrol,
+    Chase,
+    Attack,
+    /// Looking around the last known position of a lost target
+    Search,
+    /// Running away with low health
+    Flee,
+    /// Briefly unable to act after a heavy hit
+    Stunned,
+    Dead,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct Enemy {
     pub id: u32,
 }
 
+// How clients draw an enemy, set from its archetype
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct EnemyAppearance {
+    pub model: Option<String>, // Scene path, a capsule is drawn when empty
+    pub color: [f32; 3],
+    pub radius: f32,
+    pub half_height: f32,
+}
+
 #[derive(Component, Serialize, Deserialize, Clone)]
 pub struct EnemyPosition {
     pub x: f32,
     pub y: f32,
     pub z: f32,
 }
+
+// Marks an enemy as a boss, clients show a health bar for it
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Boss {
+    pub name: String,
+    pub phase: u8, // 0 is the first phase
+}
+
+// Ground zone that is about to be hit by a boss area attack
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct AreaAttackWarning {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub radius: f32,
+    pub duration: f32, // Seconds between the warning and the hit
+}
+
+// Enemy projectile in flight, simulated on the server and drawn by clients
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct Projectile {
+    pub x: f32,
+    pub y: f32,
+    pub z: f32,
+    pub radius: f32,
+}
+
+// Match timer on a replicated singleton entity
+#[derive(Component, Serialize, Deserialize, Clone)]
+pub struct MatchClock {
+    pub started_at: f64, // Server time when the current match started
+    pub duration: f32,
+}
+
+// Wave status on a replicated singleton entity
+#[derive(Component, Serialize, Deserialize, Clone, PartialEq)]
+pub struct WaveInfo {
+    pub wave: u32,
+    pub countdown: u32, // Seconds until the next wave, 0 while a wave is running
+    pub enemies_remaining: u32,
+}
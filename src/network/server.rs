@@ -1,5 +1,6 @@
 use bevy::prelude::*;
-use bevy_replicon::prelude::RepliconChannels;
+use bevy_replicon::prelude::{ClientId, RepliconChannels};
+use bevy_replicon::shared::backend::connected_client::NetworkId;
 use bevy_replicon_renet::{
     renet::{ConnectionConfig, RenetServer, ServerEvent},
     RenetChannelsExt,
@@ -10,6 +11,8 @@ use std::{
     time::SystemTime,
 };
 
+use super::protocol::HOST_CLIENT_ID;
+use super::protocol::MAX_CLIENTS;
 use super::protocol::PORT;
 use super::protocol::PROTOCOL_ID;
 
@@ -24,7 +27,7 @@ pub fn setup_server(mut commands: Commands, channels: Res<RepliconChannels>) {
 
     let server_config = ServerConfig {
         current_time,
-        max_clients: 10,
+        max_clients: MAX_CLIENTS,
         protocol_id: PROTOCOL_ID,
         public_addresses: vec![server_addr],
         authentication: ServerAuthentication::Unsecure,
@@ -61,3 +64,15 @@ pub fn server_connection_system(mut server_events: MessageReader<ServerEvent>) {
         }
     }
 }
+
+// Resolve the sender of a client message to the id used by its Player entity.
+// In host mode the local client's messages arrive from ClientId::Server.
+pub fn sender_client_id(client_id: ClientId, client_entities: &Query<&NetworkId>) -> Option<u64> {
+    match client_id {
+        ClientId::Client(entity) => client_entities
+            .get(entity)
+            .ok()
+            .map(|network_id| network_id.get()),
+        ClientId::Server => Some(HOST_CLIENT_ID),
+    }
+}